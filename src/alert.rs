@@ -0,0 +1,142 @@
+use game_data_reader::{game_data::Games, log::Log, update::Sample};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Comparison {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+    Equal,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 5] = [Comparison::Above, Comparison::AtLeast, Comparison::Below, Comparison::AtMost, Comparison::Equal];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Above => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::Below => "<",
+            Comparison::AtMost => "<=",
+            Comparison::Equal => "=",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|comparison| comparison.symbol() == symbol)
+    }
+
+    fn test(&self, value: f32, level: f32) -> bool {
+        match self {
+            Comparison::Above => value > level,
+            Comparison::AtLeast => value >= level,
+            Comparison::Below => value < level,
+            Comparison::AtMost => value <= level,
+            Comparison::Equal => value == level,
+        }
+    }
+}
+
+//fires once when a sample starts matching, and again only after it stopped matching in between
+pub struct Rule {
+    pub game: String, //game id, empty for every game
+    pub value: String, //sample name, e.g. rank or active_enemies
+    pub comparison: Comparison,
+    pub level: f32,
+    pub command: String, //run through the shell when the rule fires, empty for none
+    pub enabled: bool,
+    active: bool,
+}
+
+impl Rule {
+    pub fn new(game: &str, value: &str, comparison: Comparison, level: f32) -> Self {
+        Self {
+            game: game.to_string(),
+            value: value.to_string(),
+            comparison,
+            level,
+            command: String::new(),
+            enabled: true,
+            active: false,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} {} {}", self.value, self.comparison.symbol(), self.level)
+    }
+}
+
+//checks the samples against every enabled rule. returns a message for each rule that fired,
+//which is also logged, and starts the rule's command if it has one
+pub fn check(rules: &mut [Rule], game: &Games, samples: &[Sample], log: &mut Log) -> Vec<String> {
+    let mut fired = Vec::new();
+
+    for rule in rules.iter_mut().filter(|rule| rule.enabled) {
+        if !rule.game.trim().is_empty() && !game.matches(&rule.game.trim().to_ascii_lowercase()) {
+            continue;
+        }
+
+        for sample in samples.iter().filter(|sample| sample.name == rule.value.trim()) {
+            let active = rule.comparison.test(sample.value, rule.level);
+
+            if active && !rule.active {
+                let message = format!("alert in {}: {} ({})", game.id(), rule.describe(), sample.value);
+                log.warning(&message);
+
+                if !rule.command.trim().is_empty() {
+                    if let Err(e) = run_command(&rule.command, game, sample) {
+                        log.error(format!("unable to run alert command {}: {e}", rule.command));
+                    }
+                }
+
+                fired.push(message);
+            }
+
+            rule.active = active;
+        }
+    }
+
+    fired
+}
+
+//forgets which rules are currently matching, so they fire again for the next game
+pub fn reset(rules: &mut [Rule]) {
+    for rule in rules {
+        rule.active = false;
+    }
+}
+
+//the command gets the game, value name and value in environment variables. it is waited for
+//on its own thread so the reader doesn't block and finished commands don't linger as zombies
+fn run_command(command: &str, game: &Games, sample: &Sample) -> std::io::Result<()> {
+    let mut process = match cfg!(windows) {
+        true => {
+            let mut process = std::process::Command::new("cmd");
+            process.arg("/C").arg(command);
+            process
+        }
+
+        false => {
+            let mut process = std::process::Command::new("sh");
+            process.arg("-c").arg(command);
+            process
+        }
+    };
+
+    //the gui has no console, without this every alert would flash up a cmd window
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        process.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = process
+    .env("GDR_GAME", game.id())
+    .env("GDR_NAME", &sample.name)
+    .env("GDR_VALUE", sample.value.to_string())
+    .spawn()?;
+
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use game_data_reader::{game_data::{Emulator, Games}, update::Filter};
+
+pub const USAGE: &str = "\
+usage: game-data-reader [options]
+
+  --config <path>      config file to load and save (default: app.cfg)
+  --pid <n>            only attach to the process with this id
+  --emulator <name>    only attach to this emulator (bsnes, mame)
+  --game <id>          only attach to this game, e.g. gradius3 or gradius3_snes
+  --rate <n>           updates per second, 0.01 to 1000
+  --record <file>      record every attached game to this file (.csv or .jsonl)
+  --headless           run without a window and print samples to stdout
+  --json               print json lines instead of text in headless mode
+  --replay <file>      recording to export, repeat it to compare runs (default: the --record file)
+  --export <file>      write the rank graph of the recording to this .svg or .png file and exit
+  --software           draw without opengl, used automatically without opengl 3.3 or es 3.0
+  --help               show this message";
+
+const RATE_RANGE: std::ops::RangeInclusive<f32> = 0.01 ..= 1000.0;
+
+//command line flags override the values loaded from the config
+#[derive(Default)]
+pub struct Args {
+    pub config: Option<PathBuf>,
+    pub pid: Option<u32>,
+    pub emulator: Option<Emulator>,
+    pub game: Option<String>,
+    pub rate: Option<f32>,
+    pub record: Option<PathBuf>,
+    pub headless: bool,
+    pub json: bool,
+    pub replay: Vec<PathBuf>,
+    pub export: Option<PathBuf>,
+    pub software: bool,
+    pub help: bool,
+}
+
+impl Args {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+
+            match arg.as_str() {
+                "--config" => parsed.config = Some(PathBuf::from(value()?)),
+
+                "--pid" => {
+                    let pid = value()?;
+                    parsed.pid = Some(pid.parse().map_err(|_| format!("invalid process id: {pid}"))?);
+                }
+
+                "--emulator" => {
+                    let name = value()?;
+                    parsed.emulator = Some(Emulator::from_name(&name).ok_or(format!("unknown emulator: {name}"))?);
+                }
+
+                "--game" => {
+                    let name = value()?.to_ascii_lowercase();
+
+                    if !Games::ALL.iter().any(|game| game.matches(&name)) {
+                        return Err(format!("unknown game: {name}"));
+                    }
+
+                    parsed.game = Some(name);
+                }
+
+                "--rate" => {
+                    let rate = value()?;
+
+                    //clamped so the interval between updates stays a sane duration
+                    match rate.parse::<f32>() {
+                        Ok(rate2) if rate2.is_finite() && rate2 > 0.0 => parsed.rate = Some(rate2.clamp(*RATE_RANGE.start(), *RATE_RANGE.end())),
+                        _ => return Err(format!("invalid rate: {rate}")),
+                    }
+                }
+
+                "--record" => parsed.record = Some(PathBuf::from(value()?)),
+                "--headless" => parsed.headless = true,
+                "--json" => parsed.json = true,
+                "--replay" => parsed.replay.push(PathBuf::from(value()?)),
+                "--export" => parsed.export = Some(PathBuf::from(value()?)),
+                "--software" => parsed.software = true,
+                "--help" | "-h" => parsed.help = true,
+                _ => return Err(format!("unknown argument: {arg}")),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn filter(&self) -> Filter {
+        Filter {
+            pid: self.pid,
+            emulator: self.emulator,
+            game: self.game.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(value: &str) -> Result<Option<f32>, String> {
+        Args::parse(["--rate".to_string(), value.to_string()].into_iter()).map(|args| args.rate)
+    }
+
+    #[test]
+    fn rates() {
+        assert_eq!(rate("10"), Ok(Some(10.0)));
+        assert_eq!(rate("1e-20"), Ok(Some(0.01)));
+        assert_eq!(rate("1e9"), Ok(Some(1000.0)));
+
+        for invalid in ["inf", "-inf", "NaN", "0", "-5", "fast"] {
+            assert!(rate(invalid).is_err(), "{invalid}");
+        }
+    }
+}
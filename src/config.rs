@@ -0,0 +1,629 @@
+use std::path::{Path, PathBuf};
+
+use miniserde::json::{self, Number, Object, Value};
+
+//bump when a field is renamed or changes meaning and add a step to `migrate`. new fields don't need a bump,
+//missing fields are filled in from `Save::default`
+const VERSION: u64 = 4;
+
+const FILE_NAME: &str = "app.cfg";
+const DIR_NAME: &str = "game-data-reader";
+
+#[derive(miniserde::Serialize, miniserde::Deserialize, Debug)]
+pub struct Save {
+    pub version: u64,
+
+    //main window
+    pub window_size: (u32, u32), //physical pixels
+    pub window_pos: Option<(i32, i32)>, //none lets the os place it
+    pub overlay: bool, //borderless, transparent and always on top, showing only the graphs
+    pub overlay_click_through: bool,
+
+    //egui windows
+    pub windows: Vec<WindowSave>,
+    pub log_open: bool,
+    pub stats_open: bool,
+    pub text_files_open: bool,
+    pub alerts_open: bool,
+
+    pub timer_ticks: i8,
+
+    //rank graph
+    pub graph: GraphSave,
+    pub profiles: Vec<ProfileSave>, //graph settings for games that don't use the global ones
+    pub death_drop: u8,
+
+    pub record_format: String, //extension of the format, "csv" or "jsonl"
+    pub server_addr: String,
+    pub text_files: Vec<TextFileSave>,
+    pub alerts: Vec<AlertSave>,
+}
+
+#[derive(miniserde::Serialize, miniserde::Deserialize, Debug, Clone)]
+pub struct GraphSave {
+    pub data_point_len: u16,
+    pub gradient: Vec<String>, //"#rrggbb" colors from the lowest to the highest rank
+    pub segment_colors: bool,
+    pub aspect: f32,
+    pub bands: Vec<BandSave>,
+}
+
+#[derive(miniserde::Serialize, miniserde::Deserialize, Debug, Clone)]
+pub struct BandSave {
+    pub from: f32,
+    pub to: f32,
+    pub label: String,
+    pub color: String, //"#rrggbb"
+}
+
+#[derive(miniserde::Serialize, miniserde::Deserialize, Debug, Clone)]
+pub struct WindowSave {
+    pub id: String,
+    pub pos: (f32, f32),
+    pub width: f32, //of the contents, without the window frame
+    pub height: Option<f32>, //of the contents, without the frame and title bar. none in windows saved before heights were
+}
+
+#[derive(miniserde::Serialize, miniserde::Deserialize, Debug, Clone)]
+pub struct ProfileSave {
+    pub game: String, //game id
+    pub graph: GraphSave,
+}
+
+#[derive(miniserde::Serialize, miniserde::Deserialize, Debug)]
+pub struct TextFileSave {
+    pub path: String,
+    pub template: String,
+    pub enabled: bool,
+}
+
+#[derive(miniserde::Serialize, miniserde::Deserialize, Debug)]
+pub struct AlertSave {
+    pub game: String, //game id, empty for every game
+    pub value: String,
+    pub comparison: String, //">", ">=", "<", "<=" or "="
+    pub level: f32,
+    pub command: String,
+    pub enabled: bool,
+}
+
+impl Default for Save {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+
+            window_size: (1024, 768),
+            window_pos: None,
+            overlay: false,
+            overlay_click_through: false,
+
+            windows: vec![WindowSave {
+                id: "rank".to_string(),
+                pos: (20.0, 20.0),
+                width: 418.0,
+                height: None,
+            }],
+            log_open: false,
+            stats_open: false,
+            text_files_open: false,
+            alerts_open: false,
+
+            timer_ticks: 100,
+
+            graph: GraphSave::default(),
+            profiles: Vec::new(),
+            death_drop: 2,
+
+            record_format: "csv".to_string(),
+            server_addr: "127.0.0.1:8080".to_string(),
+            text_files: vec![TextFileSave {
+                path: "rank.txt".to_string(),
+                template: "Rank: {rank}/{max}".to_string(),
+                enabled: false, //opt in, a fresh install shouldn't write files on its own
+            }],
+            alerts: Vec::new(),
+        }
+    }
+}
+
+impl Default for GraphSave {
+    fn default() -> Self {
+        Self {
+            data_point_len: 240,
+            gradient: vec!["#00ff00".to_string(), "#ff0000".to_string()],
+            segment_colors: true,
+            aspect: 3.7,
+            bands: Vec::new(),
+        }
+    }
+}
+
+//never fails. a config that can't be read at all is moved aside so it isn't overwritten on exit, the returned
+//message says what happened and is meant for the log
+pub fn load(path: &Path) -> (Save, Option<String>) {
+    //a file that can't be read, e.g. not utf-8 or no permission, is backed up like one that can't be parsed,
+    //otherwise it would be overwritten on exit
+    let result = match std::fs::read_to_string(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return (Save::default(), None),
+        Ok(text) if text.trim().is_empty() => return (Save::default(), None), //created to turn on portable mode
+        Ok(text) => parse(&text),
+        Err(e) => Err(e.to_string()),
+    };
+
+    match result {
+        Ok((save, None)) => (save, None),
+        Ok((save, Some(note))) => (save, Some(format!("{}: {note}", path.display()))),
+
+        Err(e) => {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            let backup = PathBuf::from(backup);
+
+            let message = match std::fs::rename(path, &backup) {
+                Ok(()) => format!("unable to load {} ({e}), moved it to {} and using defaults", path.display(), backup.display()),
+                Err(e2) => format!("unable to load {} ({e}) or back it up ({e2}), using defaults", path.display()),
+            };
+
+            (Save::default(), Some(message))
+        }
+    }
+}
+
+pub fn write(path: &Path, save: &Save) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    std::fs::write(path, json::to_string(save))
+}
+
+//an app.cfg next to the executable means portable mode and is used as is. otherwise the config lives in the
+//platform config directory, taking over an app.cfg from the working directory the first time (where older versions kept it)
+pub fn default_path() -> (PathBuf, Option<String>) {
+    let exe_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    if let Some(exe_dir2) = exe_dir {
+        let portable = exe_dir2.join(FILE_NAME);
+
+        if portable.exists() {
+            return (portable, None);
+        }
+    }
+
+    let path = match config_dir() {
+        Some(dir) => dir.join(DIR_NAME).join(FILE_NAME),
+        None => return (PathBuf::from(FILE_NAME), None),
+    };
+
+    let old_path = Path::new(FILE_NAME);
+    let mut note = None;
+
+    if !path.exists() && old_path.exists() {
+        let copied = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| std::fs::copy(old_path, &path));
+
+        note = Some(match copied {
+            Ok(_) => format!("copied settings from {} to {}", old_path.display(), path.display()),
+            Err(e) => format!("unable to copy {} to {}: {e}", old_path.display(), path.display()),
+        });
+    }
+
+    (path, note)
+}
+
+//where the log, recordings and exports go: the config's folder, made absolute so the ui can show where that is
+pub fn data_dir(config_path: &Path) -> PathBuf {
+    let dir = config_path.parent().unwrap_or(Path::new(""));
+
+    match dir.is_absolute() {
+        true => dir.to_path_buf(),
+        false => std::env::current_dir().map(|cwd| cwd.join(dir)).unwrap_or_else(|_| dir.to_path_buf()),
+    }
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+
+    match cfg!(windows) {
+        true => var("APPDATA"),
+
+        false => match cfg!(target_os = "macos") {
+            true => var("HOME").map(|home| home.join("Library").join("Application Support")),
+            false => var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config"))),
+        },
+    }
+}
+
+fn parse(text: &str) -> Result<(Save, Option<String>), String> {
+    let mut object = match json::from_str::<Value>(text) {
+        Ok(Value::Object(object)) => object,
+        Ok(_) => return Err("not a json object".to_string()),
+        Err(_) => return Err("not valid json".to_string()),
+    };
+
+    let version = match object.get("version") {
+        None => 0, //written before configs were versioned
+        Some(Value::Number(Number::U64(version))) => *version,
+        Some(_) => return Err("invalid version".to_string()),
+    };
+
+    migrate(&mut object, version);
+
+    let defaults = match json::from_str::<Value>(&json::to_string(&Save::default())) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => unreachable!(),
+    };
+
+    //fields that are missing or don't fit their type fall back to the default, one at a time,
+    //so a single bad value doesn't throw away the rest of the config
+    let mut reset = Vec::new();
+
+    //profiles are written with fewer graph fields when older, same as the global graph
+    if let (Some(Value::Array(profiles)), Some(Value::Object(graph_defaults))) = (object.get_mut("profiles"), defaults.get("graph")) {
+        for profile in profiles.iter_mut() {
+            if let Value::Object(profile2) = profile {
+                if let Some(Value::Object(graph)) = profile2.get_mut("graph") {
+                    fill_missing(graph, graph_defaults);
+                }
+            }
+        }
+    }
+
+    for (key, default) in defaults.iter() {
+        //nested settings only missing some fields keep the ones they have
+        if let (Some(Value::Object(nested)), Value::Object(nested_defaults)) = (object.get_mut(key), default) {
+            fill_missing(nested, nested_defaults);
+        }
+
+        let valid = match object.get(key) {
+            Some(value) => {
+                let mut test = defaults.clone();
+                test.insert(key.clone(), value.clone());
+                to_save(test).is_some()
+            }
+
+            None => false,
+        };
+
+        if !valid {
+            if object.contains_key(key) {
+                reset.push(key.clone());
+            }
+
+            object.insert(key.clone(), default.clone());
+        }
+    }
+
+    object.insert("version".to_string(), Value::Number(Number::U64(VERSION)));
+
+    let save = to_save(object).ok_or("unable to read fields")?;
+
+    let note = match (version > VERSION, reset.is_empty()) {
+        (true, _) => Some(format!("written by a newer version (config version {version}), unknown settings are ignored")),
+        (false, false) => Some(format!("invalid values reset to defaults: {}", reset.join(", "))),
+        (false, true) => None,
+    };
+
+    Ok((save, note))
+}
+
+fn fill_missing(object: &mut Object, defaults: &Object) {
+    for (key, default) in defaults.iter() {
+        if !object.contains_key(key) {
+            object.insert(key.clone(), default.clone());
+        }
+    }
+}
+
+fn to_save(object: Object) -> Option<Save> {
+    json::from_str(&json::to_string(&Value::Object(object))).ok()
+}
+
+//turns the version 2 rank window fields of a graph object into a window object
+fn take_rank_window(graph: &mut Object, id: String) -> Option<Value> {
+    let pos = graph.remove("rank_window_pos");
+    let right = graph.remove("rank_window_width");
+
+    let (pos2, right2) = match (pos, right) {
+        (Some(Value::Array(pos2)), Some(Value::Number(right2))) => (pos2, right2),
+        _ => return None,
+    };
+
+    let x = match pos2.first() {
+        Some(Value::Number(x)) => number(x),
+        _ => return None,
+    };
+
+    let mut window = Object::new();
+    window.insert("id".to_string(), Value::String(id));
+    window.insert("pos".to_string(), Value::Array(pos2));
+    window.insert("width".to_string(), Value::Number(Number::F64((number(&right2) - x).max(100.0))));
+    Some(Value::Object(window))
+}
+
+fn take_gradient(graph: &mut Object) {
+    let mut stops = [[0u8; 3]; 2];
+
+    for (x, key) in ["color_r", "color_g", "color_b"].into_iter().enumerate() {
+        match graph.remove(key) {
+            Some(Value::Array(channel)) if channel.len() == 2 => {
+                for (y, value) in channel.iter().enumerate() {
+                    if let Value::Number(value2) = value {
+                        stops[y][x] = number(value2).clamp(0.0, 255.0) as u8;
+                    }
+                }
+            }
+
+            _ => return, //left for the defaults
+        }
+    }
+
+    let gradient = stops.iter().map(|rgb| Value::String(hex(*rgb))).collect();
+    graph.insert("gradient".to_string(), Value::Array(gradient));
+}
+
+pub fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+pub fn parse_hex(hex: &str) -> Option<[u8; 3]> {
+    let digits = hex.trim().trim_start_matches('#');
+
+    if digits.len() != 6 || !digits.is_ascii() {
+        return None;
+    }
+
+    let mut rgb = [0; 3];
+    for x in 0 .. 3 {
+        rgb[x] = u8::from_str_radix(&digits[x * 2 .. x * 2 + 2], 16).ok()?;
+    }
+
+    Some(rgb)
+}
+
+fn number(number: &Number) -> f64 {
+    match number {
+        Number::U64(x) => *x as f64,
+        Number::I64(x) => *x as f64,
+        Number::F64(x) => *x,
+    }
+}
+
+//brings a config written by an older version up to the current shape, one version at a time
+fn migrate(object: &mut Object, version: u64) {
+    for from in version .. VERSION {
+        match from {
+            0 => (), //only added the version field, everything else is filled in from the defaults
+
+            //rank graph settings moved into their own object so games can have their own copy
+            1 => {
+                let mut graph = Object::new();
+
+                for key in ["rank_window_pos", "rank_window_width", "data_point_len", "color_r", "color_g", "color_b", "aspect"] {
+                    if let Some(value) = object.remove(key) {
+                        graph.insert(key.to_string(), value);
+                    }
+                }
+
+                object.insert("graph".to_string(), Value::Object(graph));
+            }
+
+            //the rank window's position and right edge moved into the list of window positions. the right edge was
+            //saved 12 pixels short, which happens to be the window frame, so it turns into the content width directly
+            2 => {
+                let mut windows = Vec::new();
+
+                if let Some(Value::Object(graph)) = object.get_mut("graph") {
+                    windows.extend(take_rank_window(graph, "rank".to_string()));
+                }
+
+                if let Some(Value::Array(profiles)) = object.get_mut("profiles") {
+                    for profile in profiles.iter_mut() {
+                        if let Value::Object(profile2) = profile {
+                            let id = match profile2.get("game") {
+                                Some(Value::String(game)) => format!("rank/{game}"),
+                                _ => continue,
+                            };
+
+                            if let Some(Value::Object(graph)) = profile2.get_mut("graph") {
+                                windows.extend(take_rank_window(graph, id));
+                            }
+                        }
+                    }
+                }
+
+                object.insert("windows".to_string(), Value::Array(windows.into_iter().collect()));
+            }
+
+            //the low and high colors, saved per channel, turned into a list of gradient stops
+            3 => {
+                if let Some(Value::Object(graph)) = object.get_mut("graph") {
+                    take_gradient(graph);
+                }
+
+                if let Some(Value::Array(profiles)) = object.get_mut("profiles") {
+                    for profile in profiles.iter_mut() {
+                        if let Value::Object(profile2) = profile {
+                            if let Some(Value::Object(graph)) = profile2.get_mut("graph") {
+                                take_gradient(graph);
+                            }
+                        }
+                    }
+                }
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //verbatim from the write_cfg of before configs were versioned: no version, only these fields, the rank
+    //window's right edge minus 12 as its width and an f32 aspect written as f64
+    const V0: &str = r#"{"window_size":[1280,720],"timer_ticks":75,"rank_window_pos":[35.5,60.0],"rank_window_width":518.5,"data_point_len":300,"color_r":[10,250],"color_g":[240,20],"color_b":[0,5],"aspect":3.200000047683716}"#;
+
+    //flat rank settings, colors saved per channel as (low, high)
+    const V1: &str = r#"{
+        "version": 1,
+        "window_size": [800, 600],
+        "timer_ticks": 50,
+        "rank_window_pos": [30.0, 40.0],
+        "rank_window_width": 512.0,
+        "data_point_len": 120,
+        "color_r": [10, 250],
+        "color_g": [240, 20],
+        "color_b": [0, 5],
+        "aspect": 2.5,
+        "death_drop": 3,
+        "record_format": "jsonl",
+        "server_addr": "127.0.0.1:9000",
+        "text_files": [{"path": "rank.txt", "template": "{rank}", "enabled": true}]
+    }"#;
+
+    //window positions in their own list, graphs still with per channel colors, a profile from before bands existed
+    const V3: &str = r##"{
+        "version": 3,
+        "window_size": [1024, 768],
+        "window_pos": [100, 50],
+        "windows": [{"id": "rank", "pos": [20.0, 20.0], "width": 438.0}, {"id": "log", "pos": [500.0, 20.0], "width": 300.0}],
+        "timer_ticks": 100,
+        "graph": {
+            "data_point_len": 240,
+            "color_r": [0, 255],
+            "color_g": [255, 0],
+            "color_b": [0, 0],
+            "segment_colors": false,
+            "aspect": 3.7,
+            "bands": [{"from": 12.0, "to": 15.0, "label": "hard", "color": "#c82828"}]
+        },
+        "profiles": [{
+            "game": "gradius3_snes",
+            "graph": {"data_point_len": 100, "color_r": [255, 0], "color_g": [0, 0], "color_b": [0, 255], "segment_colors": true, "aspect": 2.0}
+        }],
+        "death_drop": 2,
+        "record_format": "csv",
+        "server_addr": "127.0.0.1:8080",
+        "text_files": []
+    }"##;
+
+    #[test]
+    fn migrate_v0() {
+        let (save, note) = parse(V0).unwrap();
+
+        assert!(note.is_none(), "{note:?}");
+        assert_eq!(save.version, VERSION);
+        assert_eq!(save.window_size, (1280, 720));
+        assert_eq!(save.timer_ticks, 75);
+
+        assert_eq!(save.graph.data_point_len, 300);
+        assert_eq!(save.graph.aspect, 3.2);
+        assert_eq!(save.graph.gradient, ["#0af000", "#fa1405"]);
+        assert!(save.graph.bands.is_empty());
+
+        assert_eq!(save.windows.len(), 1);
+        assert_eq!(save.windows[0].id, "rank");
+        assert_eq!(save.windows[0].pos, (35.5, 60.0));
+        assert_eq!(save.windows[0].width, 483.0);
+
+        //everything added since comes from the defaults
+        let defaults = Save::default();
+        assert_eq!(save.death_drop, defaults.death_drop);
+        assert_eq!(save.record_format, defaults.record_format);
+        assert_eq!(save.server_addr, defaults.server_addr);
+        assert!(save.profiles.is_empty());
+        assert!(save.alerts.is_empty());
+    }
+
+    #[test]
+    fn migrate_v1() {
+        let (save, note) = parse(V1).unwrap();
+
+        assert!(note.is_none(), "{note:?}");
+        assert_eq!(save.version, VERSION);
+        assert_eq!(save.window_size, (800, 600));
+        assert_eq!(save.timer_ticks, 50);
+        assert_eq!(save.death_drop, 3);
+        assert_eq!(save.record_format, "jsonl");
+        assert_eq!(save.server_addr, "127.0.0.1:9000");
+        assert!(save.text_files[0].enabled);
+
+        assert_eq!(save.graph.data_point_len, 120);
+        assert_eq!(save.graph.aspect, 2.5);
+        assert_eq!(save.graph.gradient, ["#0af000", "#fa1405"]);
+
+        //the saved width was the right edge
+        assert_eq!(save.windows.len(), 1);
+        assert_eq!(save.windows[0].id, "rank");
+        assert_eq!(save.windows[0].pos, (30.0, 40.0));
+        assert_eq!(save.windows[0].width, 482.0);
+        assert_eq!(save.windows[0].height, None); //sized by its contents until it's saved again
+    }
+
+    #[test]
+    fn migrate_v3() {
+        let (save, note) = parse(V3).unwrap();
+
+        assert!(note.is_none(), "{note:?}");
+        assert_eq!(save.version, VERSION);
+        assert_eq!(save.window_pos, Some((100, 50)));
+        assert_eq!(save.windows.iter().map(|window| window.id.as_str()).collect::<Vec<_>>(), ["rank", "log"]);
+
+        assert_eq!(save.graph.gradient, ["#00ff00", "#ff0000"]);
+        assert!(!save.graph.segment_colors);
+        assert_eq!(save.graph.bands.len(), 1);
+        assert_eq!(save.graph.bands[0].label, "hard");
+
+        assert_eq!(save.profiles.len(), 1);
+        assert_eq!(save.profiles[0].game, "gradius3_snes");
+        assert_eq!(save.profiles[0].graph.data_point_len, 100);
+        assert_eq!(save.profiles[0].graph.gradient, ["#ff0000", "#0000ff"]);
+        assert!(save.profiles[0].graph.bands.is_empty());
+    }
+
+    #[test]
+    fn unreadable_backed_up() {
+        let dir = std::env::temp_dir().join("game-data-reader-config-tests");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("not_utf8.cfg");
+        let backup = dir.join("not_utf8.cfg.bak");
+        let _ = std::fs::remove_file(&backup);
+        std::fs::write(&path, [b'{', 0xFF, 0xFE, b'}']).unwrap();
+
+        let (save, note) = load(&path);
+
+        assert_eq!(save.version, VERSION);
+        assert!(note.is_some_and(|note2| note2.contains("moved it to")));
+        assert!(!path.exists());
+        assert_eq!(std::fs::read(&backup).unwrap(), [b'{', 0xFF, 0xFE, b'}']);
+
+        //missing and empty files are just defaults
+        assert!(load(&path).1.is_none());
+        std::fs::write(&path, "").unwrap();
+        assert!(load(&path).1.is_none());
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&backup);
+    }
+
+    #[test]
+    fn current_round_trip() {
+        let (save, note) = parse(&json::to_string(&Save::default())).unwrap();
+
+        assert!(note.is_none(), "{note:?}");
+        assert_eq!(json::to_string(&save), json::to_string(&Save::default()));
+    }
+
+    #[test]
+    fn data_dirs() {
+        let cwd = std::env::current_dir().unwrap();
+
+        assert_eq!(data_dir(Path::new("app.cfg")), cwd);
+        assert_eq!(data_dir(Path::new("portable/app.cfg")), cwd.join("portable"));
+        assert_eq!(data_dir(&cwd.join("game-data-reader").join("app.cfg")), cwd.join("game-data-reader"));
+    }
+}
@@ -2,79 +2,157 @@ use egui::{Modifiers, RawInput};
 use glutin::{context::PossiblyCurrentContext, surface::{Surface, WindowSurface, SurfaceAttributesBuilder, GlSurface}, prelude::{GlConfig, GlDisplay, NotCurrentGlContextSurfaceAccessor}, display::GetGlDisplay};
 use raw_window_handle::HasRawWindowHandle;
 use winit::{event::{VirtualKeyCode, Event, WindowEvent, ElementState}, event_loop::ControlFlow};
-use std::{ffi::{CString, c_void}, ptr, str::from_utf8};
+use std::{ffi::{CString, c_void}, ptr};
 
 use crate::GuiState;
 
 pub struct GlutinState {
-    pub window: winit::window::Window,
     pub gl_ctx: PossiblyCurrentContext,
     pub gl_display: glutin::display::Display,
     pub gl_surface: Surface<WindowSurface>,
+    pub api: GlApi,
+
+    vao: u32,
+    vbo: u32,
+    pub tex: u32,
+    shader: u32,
+    size_location: i32, //of the `size` uniform
+
+    buffer_size: u32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GlApi {
+    Gl45, //direct state access
+    Gl33, //classic bind and draw, also used for 4.5 contexts without direct state access
+    Gles3,
+}
+
+impl GlApi {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GlApi::Gl45 => "opengl 4.5",
+            GlApi::Gl33 => "opengl 3.3",
+            GlApi::Gles3 => "opengl es 3.0",
+        }
+    }
+
+    fn dsa(&self) -> bool {
+        *self == GlApi::Gl45
+    }
+}
+
+pub enum Backend {
+    Gl(GlutinState),
+    Software(crate::software::Renderer), //for machines without gl 3.3 or gles 3, presented through gdi
 }
 
 pub struct EguiState {
-    pub glutin_state: GlutinState,
+    pub window: winit::window::Window,
+    pub backend: Backend,
 
     pub ctx: egui::Context,
     pub pos_in_points: Option<egui::Pos2>,
     pub raw_input: RawInput,
 
-    vao: u32,
-    vbo: u32,
-    pub tex: u32,
-    shader: u32,
+    window_size: (u32, u32),
+    window_pos: Option<(i32, i32)>,
+    overlay: Option<(bool, bool)>, //(overlay, click through) as last applied to the window, none before the first frame
+}
 
-    buffer_size: u32,
+//uses the newest gl the driver has and falls back to the software renderer otherwise, or when `software` is set
+pub fn setup_egui_glutin(el: &winit::event_loop::EventLoop<()>, window_size: (u32, u32), window_pos: Option<(i32, i32)>, software: bool, log: &mut crate::log::Log) -> EguiState {
+    let mut wb = winit::window::WindowBuilder::new()
+    .with_inner_size(winit::dpi::PhysicalSize::new(window_size.0, window_size.1))
+    .with_title("Game data reader 0.9")
+    .with_transparent(true); //only shows in overlay mode, the normal background is cleared opaque
 
-    window_size: (u32, u32),
+    if let Some(pos) = window_pos {
+        wb = wb.with_position(winit::dpi::PhysicalPosition::new(pos.0, pos.1));
+    }
+
+    let mut window = None;
+
+    let gl = match software {
+        true => Err("disabled with --software".to_string()),
+        false => setup_gl(el, wb.clone(), window_size, &mut window),
+    };
+
+    let backend = match gl {
+        Ok(glutin_state) => {
+            log.info(format!("rendering with {}", glutin_state.api.name()));
+            Backend::Gl(glutin_state)
+        }
+
+        Err(e) => {
+            log.warning(format!("opengl unavailable ({e}), using software rendering"));
+            Backend::Software(crate::software::Renderer::new(window_size.0 as usize, window_size.1 as usize))
+        }
+    };
+
+    let window = match window {
+        Some(window2) => window2,
+        None => wb.build(el).expect("unable to create window"),
+    };
+
+    EguiState {
+        window,
+        backend,
+
+        ctx: egui::Context::default(),
+        pos_in_points: None,
+        raw_input: egui::RawInput::default(),
+
+        window_size: window_size,
+        window_pos,
+        overlay: None,
+    }
 }
 
-pub fn setup_egui_glutin(el: &winit::event_loop::EventLoop<()>, window_size: (u32, u32)) -> EguiState {
-    let wb = winit::window::WindowBuilder::new()
-    .with_inner_size(winit::dpi::LogicalSize::new(window_size.0, window_size.1))
-    .with_title("Game data reader 0.9");
-
-    let (window, gl_config) = glutin_winit::DisplayBuilder::new()
-        .with_window_builder(Some(wb))
-        .build(&el, <_>::default(), |configs| {
-            configs
-                .filter(|c| c.srgb_capable())
-                .max_by_key(|c| c.num_samples())
-                .unwrap()
-        }).unwrap();
-
-        let window = window.unwrap();
-        let raw_window_handle = window.raw_window_handle();
-        let gl_display = gl_config.display();
-
-        let context_attributes = glutin::context::ContextAttributesBuilder::new()
-            .with_profile(glutin::context::GlProfile::Core)
-            .with_context_api(glutin::context::ContextApi::OpenGl(Some(
-                glutin::context::Version::new(4, 5),
-            )))
-            .build(Some(raw_window_handle));
-
-        let (gl_surface, gl_ctx) = {
-            let attrs = SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new().build(
-                raw_window_handle,
-                std::num::NonZeroU32::new(window_size.0).unwrap(),
-                std::num::NonZeroU32::new(window_size.1).unwrap(),
-            );
-
-            let surface = unsafe { match gl_display.create_window_surface(&gl_config, &attrs) {
-                Ok(o) => o,
-                Err(_) => todo!("omg"),
-            }};
-
-            let context = unsafe { match gl_display.create_context(&gl_config, &context_attributes) {
-                Ok(o) => o,
-                Err(_) => todo!("omg"),
-            }}.make_current(&surface).unwrap();
-            (surface, context)
-        };
+//the window is kept in `window` as soon as it exists, so the software renderer can reuse it when gl fails later on
+fn setup_gl(el: &winit::event_loop::EventLoop<()>, wb: winit::window::WindowBuilder, window_size: (u32, u32), window: &mut Option<winit::window::Window>) -> Result<GlutinState, String> {
+    //transparency is only needed for the overlay, don't give up on gl without it
+    let template = glutin::config::ConfigTemplateBuilder::new().with_alpha_size(8).with_transparency(true);
+
+    let (window2, gl_config) = glutin_winit::DisplayBuilder::new()
+        .with_window_builder(Some(wb.clone()))
+        .build(el, template, pick_config)
+        .or_else(|_| glutin_winit::DisplayBuilder::new().with_window_builder(Some(wb)).build(el, <_>::default(), pick_config))
+        .map_err(|e| e.to_string())?;
+
+    let raw_window_handle = window.insert(window2.ok_or("no window")?).raw_window_handle();
+    let gl_display = gl_config.display();
+
+    let attrs = SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new().build(
+        raw_window_handle,
+        std::num::NonZeroU32::new(window_size.0.max(1)).unwrap(),
+        std::num::NonZeroU32::new(window_size.1.max(1)).unwrap(),
+    );
+
+    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs) }.map_err(|e| e.to_string())?;
+
+    //newest first, the first one the driver accepts is used
+    let apis = [
+        (glutin::context::ContextApi::OpenGl(Some(glutin::context::Version::new(4, 5))), GlApi::Gl45),
+        (glutin::context::ContextApi::OpenGl(Some(glutin::context::Version::new(3, 3))), GlApi::Gl33),
+        (glutin::context::ContextApi::Gles(Some(glutin::context::Version::new(3, 0))), GlApi::Gles3),
+    ];
+
+    let mut errors = Vec::new();
+    let mut context = None;
+
+    for (context_api, api) in apis {
+        match setup_context(&gl_display, &gl_config, &gl_surface, raw_window_handle, context_api, api) {
+            Ok(context2) => {
+                context = Some(context2);
+                break;
+            }
+
+            Err(e) => errors.push(format!("{}: {e}", api.name())),
+        }
+    }
 
-        gl::load_with(|symbol| (gl_display.get_proc_address(&CString::new(symbol).unwrap()) as _));
+    let (gl_ctx, api, shader) = context.ok_or(errors.join(", "))?;
 
     unsafe {
         gl::Enable(gl::BLEND);
@@ -84,103 +162,241 @@ pub fn setup_egui_glutin(el: &winit::event_loop::EventLoop<()>, window_size: (u3
         gl::Enable(gl::SCISSOR_TEST);
     }
 
-    let (vao_e, vbo_e) = setup_vertex_arrays_egui();
-    let vert_e = include_str!("shader_e.vert");
-    let frag_e = include_str!("shader_e.frag");
+    let (vao_e, vbo_e) = setup_vertex_arrays_egui(api);
 
-    EguiState {
-        glutin_state: GlutinState {
-            window: window,
-            gl_ctx: gl_ctx,
-            gl_display: gl_display,
-            gl_surface: gl_surface,
-        },
+    let size_location = match api {
+        GlApi::Gl45 => 3,
+        _ => unsafe { gl::GetUniformLocation(shader, CString::new("size").unwrap().as_ptr()) },
+    };
 
-        ctx: egui::Context::default(),
-        pos_in_points: None,
-        raw_input: egui::RawInput::default(),
+    let glutin_state = GlutinState {
+        gl_ctx: gl_ctx,
+        gl_display: gl_display,
+        gl_surface: gl_surface,
+        api,
 
         vao: vao_e,
         vbo: vbo_e,
-        tex: setup_texture_egui(),
-        shader: create_program(vert_e, frag_e),
+        tex: setup_texture_egui(api),
+        shader,
+        size_location,
 
         buffer_size: 0,
+    };
 
-        window_size: window_size,
+    //only the 4.5 shader has a default size
+    set_size(&glutin_state, window_size);
+
+    Ok(glutin_state)
+}
+
+//makes a context current and builds the shaders for it, a driver that rejects either moves on to the next api
+fn setup_context(
+    gl_display: &glutin::display::Display,
+    gl_config: &glutin::config::Config,
+    gl_surface: &Surface<WindowSurface>,
+    raw_window_handle: raw_window_handle::RawWindowHandle,
+    context_api: glutin::context::ContextApi,
+    mut api: GlApi,
+) -> Result<(PossiblyCurrentContext, GlApi, u32), String> {
+    //profiles only exist for desktop gl
+    let context_attributes = match context_api {
+        glutin::context::ContextApi::OpenGl(_) => glutin::context::ContextAttributesBuilder::new().with_profile(glutin::context::GlProfile::Core),
+        glutin::context::ContextApi::Gles(_) => glutin::context::ContextAttributesBuilder::new(),
     }
+    .with_context_api(context_api)
+    .build(Some(raw_window_handle));
+
+    let gl_ctx = unsafe { gl_display.create_context(gl_config, &context_attributes) }
+        .and_then(|context| context.make_current(gl_surface))
+        .map_err(|e| e.to_string())?;
+
+    gl::load_with(|symbol| (gl_display.get_proc_address(&CString::new(symbol).unwrap()) as _));
+
+    //some drivers hand out a 4.5 context without the direct state access functions
+    if api == GlApi::Gl45 && !(gl::CreateVertexArrays::is_loaded() && gl::NamedBufferData::is_loaded() && gl::BindTextureUnit::is_loaded()) {
+        api = GlApi::Gl33;
+    }
+
+    let (vert_e, frag_e) = match api {
+        GlApi::Gl45 => (include_str!("shader_e.vert"), include_str!("shader_e.frag")),
+        GlApi::Gl33 => (include_str!("shader_e_330.vert"), include_str!("shader_e_330.frag")),
+        GlApi::Gles3 => (include_str!("shader_e_300es.vert"), include_str!("shader_e_300es.frag")),
+    };
+
+    let shader = create_program(vert_e, frag_e)?;
+    Ok((gl_ctx, api, shader))
 }
 
-pub fn paint_egui(clipped_primitives: Vec<egui::ClippedPrimitive>, egui_state: &mut EguiState) {
+fn set_size(glutin_state: &GlutinState, window_size: (u32, u32)) {
     unsafe {
-        gl::Scissor(0, 0, egui_state.window_size.0 as i32, egui_state.window_size.1 as i32);
-        gl::ClearColor(0.0, 0.1, 0.2, 1.0);
+        gl::Viewport(0, 0, window_size.0 as i32, window_size.1 as i32);
+
+        match glutin_state.api.dsa() {
+            true => gl::ProgramUniform2f(glutin_state.shader, glutin_state.size_location, window_size.0 as f32, window_size.1 as f32),
+
+            false => {
+                gl::UseProgram(glutin_state.shader);
+                gl::Uniform2f(glutin_state.size_location, window_size.0 as f32, window_size.1 as f32);
+            }
+        }
+    }
+}
+
+//prefers srgb, then transparency for the overlay, then multisampling
+fn pick_config(configs: Box<dyn Iterator<Item = glutin::config::Config> + '_>) -> glutin::config::Config {
+    configs
+        .max_by_key(|c| (c.srgb_capable(), c.supports_transparency().unwrap_or(false), c.num_samples()))
+        .unwrap()
+}
+
+//uploads the new textures, draws the frame and shows it
+pub fn paint(egui_state: &mut EguiState, textures_delta: egui::TexturesDelta, clipped_primitives: Vec<egui::ClippedPrimitive>) {
+    let transparent = matches!(egui_state.overlay, Some((true, _)));
+
+    match &mut egui_state.backend {
+        Backend::Gl(glutin_state) => {
+            update_textures(textures_delta.set, glutin_state.tex, glutin_state.api);
+            paint_egui(clipped_primitives, glutin_state, egui_state.window_size, transparent);
+            glutin_state.gl_surface.swap_buffers(&glutin_state.gl_ctx).unwrap();
+        }
+
+        //gdi has no per pixel transparency, so the overlay keeps its background here
+        Backend::Software(renderer) => {
+            renderer.update_textures(textures_delta.set);
+            renderer.paint(&clipped_primitives, egui_state.window_size.0 as usize, egui_state.window_size.1 as usize, crate::software::CLEAR_COLOR);
+            renderer.free_textures(&textures_delta.free);
+            crate::software::present(&egui_state.window, renderer);
+        }
+    }
+}
+
+pub fn paint_egui(clipped_primitives: Vec<egui::ClippedPrimitive>, glutin_state: &mut GlutinState, window_size: (u32, u32), transparent: bool) {
+    unsafe {
+        gl::Scissor(0, 0, window_size.0 as i32, window_size.1 as i32);
+        match transparent {
+            true => gl::ClearColor(0.0, 0.0, 0.0, 0.0),
+            false => gl::ClearColor(0.0, 0.1, 0.2, 1.0),
+        }
         gl::Clear(gl::COLOR_BUFFER_BIT | gl::STENCIL_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
-        gl::BindVertexArray(egui_state.vao);
-        gl::BindBuffer(gl::ARRAY_BUFFER, egui_state.vbo);
-        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, egui_state.vbo);
-        gl::UseProgram(egui_state.shader);
-        gl::BindTextureUnit(0, egui_state.tex);
+        gl::BindVertexArray(glutin_state.vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, glutin_state.vbo);
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, glutin_state.vbo);
+        gl::UseProgram(glutin_state.shader);
+
+        match glutin_state.api.dsa() {
+            true => gl::BindTextureUnit(0, glutin_state.tex),
+
+            false => {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, glutin_state.tex);
+            }
+        }
+
         gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
     }
 
     for clipped_primitive in &clipped_primitives {
         unsafe {
-            let x = clipped_primitive.clip_rect.min.x.clamp(0.0, egui_state.window_size.0 as f32);
-            let y = clipped_primitive.clip_rect.min.y.clamp(0.0, egui_state.window_size.1 as f32);
-            let width = clipped_primitive.clip_rect.max.x.clamp(x, egui_state.window_size.0 as f32) as i32;
-            let height = clipped_primitive.clip_rect.max.y.clamp(y, egui_state.window_size.1 as f32) as i32;
-            gl::Scissor(x as i32, egui_state.window_size.1 as i32 - height, width - x as i32, height - y as i32);
+            let x = clipped_primitive.clip_rect.min.x.clamp(0.0, window_size.0 as f32);
+            let y = clipped_primitive.clip_rect.min.y.clamp(0.0, window_size.1 as f32);
+            let width = clipped_primitive.clip_rect.max.x.clamp(x, window_size.0 as f32) as i32;
+            let height = clipped_primitive.clip_rect.max.y.clamp(y, window_size.1 as f32) as i32;
+            gl::Scissor(x as i32, window_size.1 as i32 - height, width - x as i32, height - y as i32);
 
             let mesh = match &clipped_primitive.primitive {
                 egui::epaint::Primitive::Mesh(mesh2) => mesh2,
-                egui::epaint::Primitive::Callback(_) => todo!(),
+                egui::epaint::Primitive::Callback(_) => continue, //not used by the ui
             };
 
             let buffer_size = ((mesh.indices.len() + (mesh.vertices.len() * 5)) * 4) as u32;
 
-            if egui_state.buffer_size < buffer_size {
-                gl::NamedBufferData(
-                    egui_state.vbo,
-                    buffer_size as isize,
-                    ptr::null(),
-                    gl::DYNAMIC_DRAW,
-                );
+            match glutin_state.api.dsa() {
+                true => {
+                    if glutin_state.buffer_size < buffer_size {
+                        gl::NamedBufferData(
+                            glutin_state.vbo,
+                            buffer_size as isize,
+                            ptr::null(),
+                            gl::DYNAMIC_DRAW,
+                        );
 
-                egui_state.buffer_size = buffer_size;
-            }
+                        glutin_state.buffer_size = buffer_size;
+                    }
+
+                    gl::NamedBufferSubData(
+                        glutin_state.vbo,
+                        0,
+                        mesh.indices.len() as isize * 4,
+                        mesh.indices.as_ptr() as *const c_void,
+                    );
+
+                    gl::NamedBufferSubData(
+                        glutin_state.vbo,
+                        mesh.indices.len() as isize * 4,
+                        mesh.vertices.len() as isize * 5 * 4,
+                        mesh.vertices.as_ptr() as *const c_void,
+                    );
 
-            gl::NamedBufferSubData(
-                egui_state.vbo,
-                0,
-                mesh.indices.len() as isize * 4,
-                mesh.indices.as_ptr() as *const c_void,
-            );
-
-            gl::NamedBufferSubData(
-                egui_state.vbo,
-                mesh.indices.len() as isize * 4,
-                mesh.vertices.len() as isize * 5 * 4,
-                mesh.vertices.as_ptr() as *const c_void,
-            );
-
-            gl::VertexArrayVertexBuffer(
-                egui_state.vao,
-                0,
-                egui_state.vbo,
-                mesh.indices.len() as isize * 4,
-                5 * 4,
-            );
+                    gl::VertexArrayVertexBuffer(
+                        glutin_state.vao,
+                        0,
+                        glutin_state.vbo,
+                        mesh.indices.len() as isize * 4,
+                        5 * 4,
+                    );
+                }
+
+                //the buffer is still bound from above, to both targets
+                false => {
+                    if glutin_state.buffer_size < buffer_size {
+                        gl::BufferData(gl::ARRAY_BUFFER, buffer_size as isize, ptr::null(), gl::DYNAMIC_DRAW);
+                        glutin_state.buffer_size = buffer_size;
+                    }
+
+                    gl::BufferSubData(gl::ARRAY_BUFFER, 0, mesh.indices.len() as isize * 4, mesh.indices.as_ptr() as *const c_void);
+
+                    gl::BufferSubData(
+                        gl::ARRAY_BUFFER,
+                        mesh.indices.len() as isize * 4,
+                        mesh.vertices.len() as isize * 5 * 4,
+                        mesh.vertices.as_ptr() as *const c_void,
+                    );
+
+                    //vertices start after the indices, which differs for every mesh
+                    let offset = mesh.indices.len() * 4;
+                    gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 5 * 4, offset as *const c_void); //vertex
+                    gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 5 * 4, (offset + 2 * 4) as *const c_void); //uv
+                    gl::VertexAttribPointer(2, 4, gl::UNSIGNED_BYTE, gl::FALSE, 5 * 4, (offset + 4 * 4) as *const c_void); //color
+                }
+            }
 
             gl::DrawElements(gl::TRIANGLES, mesh.indices.len() as i32, gl::UNSIGNED_INT, ptr::null::<c_void>());
         }
     }
 }
 
-pub fn setup_vertex_arrays_egui() -> (u32, u32) {
+pub fn setup_vertex_arrays_egui(api: GlApi) -> (u32, u32) {
     let (mut vao, mut vbo) = (0, 0);
 
+    //the attribute pointers are set for every mesh in `paint_egui`
+    if !api.dsa() {
+        unsafe {
+            gl::GenBuffers(1, &mut vbo);
+            gl::GenVertexArrays(1, &mut vao);
+
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, vbo);
+
+            gl::EnableVertexAttribArray(0);
+            gl::EnableVertexAttribArray(1);
+            gl::EnableVertexAttribArray(2);
+        }
+
+        return (vao, vbo);
+    }
+
     unsafe {
         gl::CreateBuffers(1, &mut vbo);
         gl::CreateVertexArrays(1, &mut vao);
@@ -226,9 +442,22 @@ pub fn setup_vertex_arrays_egui() -> (u32, u32) {
     (vao, vbo)
 }
 
-pub fn setup_texture_egui() -> u32 {
+pub fn setup_texture_egui(api: GlApi) -> u32 {
     let mut tex_e = 0;
 
+    if !api.dsa() {
+        unsafe {
+            gl::GenTextures(1, &mut tex_e);
+            gl::BindTexture(gl::TEXTURE_2D, tex_e);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        }
+
+        return tex_e;
+    }
+
     unsafe {
         gl::CreateTextures(gl::TEXTURE_2D, 1, &mut tex_e);
         gl::TextureParameteri(tex_e, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
@@ -240,7 +469,16 @@ pub fn setup_texture_egui() -> u32 {
     tex_e
 }
 
-pub fn update_texture_egui(tex_e: u32, pix: Vec<(u8, u8, u8, u8)>, x: usize, y: usize, width: usize, height: usize) {
+pub fn update_texture_egui(tex_e: u32, api: GlApi, pix: Vec<(u8, u8, u8, u8)>, x: usize, y: usize, width: usize, height: usize) {
+    if !api.dsa() {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, tex_e);
+            gl::TexSubImage2D(gl::TEXTURE_2D, 0, x as i32, y as i32, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE, pix.as_ptr() as *const c_void);
+        }
+
+        return;
+    }
+
     unsafe {
         gl::TextureSubImage2D(
             tex_e,
@@ -256,7 +494,17 @@ pub fn update_texture_egui(tex_e: u32, pix: Vec<(u8, u8, u8, u8)>, x: usize, y:
     }
 }
 
-pub fn upload_texture_egui(tex_e: u32, pix: Vec<(u8, u8, u8, u8)>, width: usize, height: usize) {
+pub fn upload_texture_egui(tex_e: u32, api: GlApi, pix: Vec<(u8, u8, u8, u8)>, width: usize, height: usize) {
+    //mutable storage, so unlike with TextureStorage2D the texture can be uploaded again
+    if !api.dsa() {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, tex_e);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA8 as i32, width as i32, height as i32, 0, gl::RGBA, gl::UNSIGNED_BYTE, pix.as_ptr() as *const c_void);
+        }
+
+        return;
+    }
+
     unsafe {
         gl::TextureStorage2D(
             tex_e,
@@ -280,9 +528,17 @@ pub fn upload_texture_egui(tex_e: u32, pix: Vec<(u8, u8, u8, u8)>, width: usize,
     }
 }
 
-pub fn create_program(vertex_src: &str, fragment_src: &str) -> u32 {
-    let vertex_handler = compile_shader(vertex_src, gl::VERTEX_SHADER);
-    let fragment_handler = compile_shader(fragment_src, gl::FRAGMENT_SHADER);
+pub fn create_program(vertex_src: &str, fragment_src: &str) -> Result<u32, String> {
+    let vertex_handler = compile_shader(vertex_src, gl::VERTEX_SHADER)?;
+
+    let fragment_handler = match compile_shader(fragment_src, gl::FRAGMENT_SHADER) {
+        Ok(fragment_handler2) => fragment_handler2,
+
+        Err(e) => {
+            unsafe { gl::DeleteShader(vertex_handler); }
+            return Err(e);
+        }
+    };
 
     unsafe {
         let program_id = gl::CreateProgram();
@@ -290,16 +546,29 @@ pub fn create_program(vertex_src: &str, fragment_src: &str) -> u32 {
         gl::AttachShader(program_id, fragment_handler);
 
         gl::LinkProgram(program_id);
-        gl::UseProgram(program_id);
 
         gl::DeleteShader(vertex_handler);
         gl::DeleteShader(fragment_handler);
 
-        program_id
+        let mut success = i32::from(gl::FALSE);
+        gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
+
+        if success != i32::from(gl::TRUE) {
+            let mut len = 0;
+            gl::GetProgramiv(program_id, gl::INFO_LOG_LENGTH, &mut len);
+
+            let mut info_log = vec![0u8; len.max(1) as usize];
+            gl::GetProgramInfoLog(program_id, len, ptr::null_mut(), info_log.as_mut_ptr() as *mut i8);
+            gl::DeleteProgram(program_id);
+            return Err(format!("shader linking failed: {}", String::from_utf8_lossy(&info_log).trim_end_matches('\0').trim()));
+        }
+
+        gl::UseProgram(program_id);
+        Ok(program_id)
     }
 }
 
-fn compile_shader(source: &str, shader_type: u32) -> u32 {
+fn compile_shader(source: &str, shader_type: u32) -> Result<u32, String> {
     unsafe {
         let shader_handler = gl::CreateShader(shader_type);
         let c_str = CString::new(source.as_bytes()).unwrap();
@@ -313,17 +582,17 @@ fn compile_shader(source: &str, shader_type: u32) -> u32 {
             let mut len = 0;
             gl::GetShaderiv(shader_handler, gl::INFO_LOG_LENGTH, &mut len);
 
-            let mut info_log = vec![0; len as usize];
+            let mut info_log = vec![0u8; len.max(1) as usize];
             gl::GetShaderInfoLog(shader_handler, len, ptr::null_mut(), info_log.as_mut_ptr() as *mut i8);
-            println!("Shader compilation failed: {}", from_utf8(&info_log).unwrap());
-            std::process::exit(-1);
+            gl::DeleteShader(shader_handler);
+            return Err(format!("shader compilation failed: {}", String::from_utf8_lossy(&info_log).trim_end_matches('\0').trim()));
         }
 
-        shader_handler
+        Ok(shader_handler)
     }
 }
 
-pub fn update_textures(tex_set: Vec<(egui::TextureId, egui::epaint::ImageDelta)>, tex_e: u32) {
+pub fn update_textures(tex_set: Vec<(egui::TextureId, egui::epaint::ImageDelta)>, tex_e: u32, api: GlApi) {
     for (id, image_delta) in &tex_set {
         let pixels: Vec<(u8, u8, u8, u8)> = match &image_delta.image {
             egui::ImageData::Color(image) => {
@@ -340,9 +609,9 @@ pub fn update_textures(tex_set: Vec<(egui::TextureId, egui::epaint::ImageDelta)>
         let height = image_delta.image.height();
 
         if let Some(pos) = image_delta.pos {
-            update_texture_egui(tex_e, pixels, pos[0], pos[1], width, height)
+            update_texture_egui(tex_e, api, pixels, pos[0], pos[1], width, height)
         } else {
-            upload_texture_egui(tex_e, pixels, width, height);
+            upload_texture_egui(tex_e, api, pixels, width, height);
         }
     }
 }
@@ -422,27 +691,75 @@ fn is_printable_char(chr: char) -> bool {
     !is_in_private_use_area && !chr.is_ascii_control()
 }
 
-fn write_cfg(egui_state: &EguiState, gui_state: &GuiState) {
-    let save = crate::Save {
+//applies overlay mode to the window when it was switched since the last frame
+pub fn apply_overlay(egui_state: &mut EguiState, gui_state: &GuiState) {
+    let overlay = (gui_state.overlay, gui_state.overlay && gui_state.overlay_click_through);
+
+    if egui_state.overlay == Some(overlay) {
+        return;
+    }
+
+    let window = &egui_state.window;
+    window.set_decorations(!overlay.0);
+    window.set_always_on_top(overlay.0);
+
+    //not supported everywhere, the window then just stays clickable
+    let _ = window.set_cursor_hittest(!overlay.1);
+
+    egui_state.overlay = Some(overlay);
+}
+
+fn write_cfg(egui_state: &EguiState, gui_state: &mut GuiState) {
+    let save = crate::config::Save {
         window_size: egui_state.window_size,
+        window_pos: egui_state.window_pos,
+        overlay: gui_state.overlay,
+        overlay_click_through: gui_state.overlay_click_through,
+        windows: gui_state.windows.clone(),
+        log_open: gui_state.log_open,
+        stats_open: gui_state.stats_open,
+        text_files_open: gui_state.text_files_open,
+        alerts_open: gui_state.alerts_open,
 
         timer_ticks: gui_state.timer_ticks,
 
-        rank_window_pos: gui_state.graph.default_window_pos,
-        rank_window_width: gui_state.graph.default_window_width - 12.0, //why is -12 necessary? probably doing something wrong
-        color_r: (gui_state.graph.color_start[0], gui_state.graph.color_end[0]),
-        color_g: (gui_state.graph.color_start[1], gui_state.graph.color_end[1]),
-        color_b: (gui_state.graph.color_start[2], gui_state.graph.color_end[2]),
-        data_point_len: gui_state.graph.data_point_len,
-        aspect: gui_state.graph.aspect,
+        graph: gui_state.global_graph.clone(),
+        profiles: gui_state.profiles.clone(),
+        death_drop: gui_state.death_drop,
+
+        record_format: gui_state.record_format.extension().to_string(),
+        server_addr: gui_state.server_addr.clone(),
+        text_files: gui_state.text_files.iter().map(|text_file| crate::config::TextFileSave {
+            path: text_file.path.clone(),
+            template: text_file.template.clone(),
+            enabled: text_file.enabled,
+        }).collect(),
+        alerts: gui_state.alerts.iter().map(|rule| crate::config::AlertSave {
+            game: rule.game.clone(),
+            value: rule.value.clone(),
+            comparison: rule.comparison.symbol().to_string(),
+            level: rule.level,
+            command: rule.command.clone(),
+            enabled: rule.enabled,
+        }).collect(),
+
+        ..Default::default()
     };
 
-    std::fs::write("app.cfg", miniserde::json::to_string(&save)).expect("unable to write cfg");
+    //on the way out, so only the log file gets to tell about it
+    if let Err(e) = crate::config::write(&gui_state.config_path, &save) {
+        gui_state.log.error(format!("unable to write {}: {e}", gui_state.config_path.display()));
+    }
 }
 
-pub fn event_handling(event: Event<()>, control_flow: &mut ControlFlow, egui_state: &mut EguiState, gui_state: &GuiState) {
+pub fn event_handling(event: Event<()>, control_flow: &mut ControlFlow, egui_state: &mut EguiState, gui_state: &mut GuiState) {
     match event {
-        Event::LoopDestroyed => write_cfg(egui_state, gui_state),
+        Event::LoopDestroyed => {
+            crate::stop_recording(gui_state);
+            crate::store_profile(gui_state);
+            write_cfg(egui_state, gui_state);
+            gui_state.log.flush(); //the event loop exits the process without dropping it
+        }
 
         Event::WindowEvent{event, ..} => {
             match event {
@@ -468,6 +785,10 @@ pub fn event_handling(event: Event<()>, control_flow: &mut ControlFlow, egui_sta
                             egui_state.raw_input.modifiers.shift = pressed;
                         }
 
+                        if keycode == VirtualKeyCode::F10 && pressed {
+                            gui_state.overlay = !gui_state.overlay;
+                        }
+
                         if let Some(key) = translate_virtual_key_code(keycode) {
                             if key == egui::Key::Escape && pressed {
                                 *control_flow = ControlFlow::Exit
@@ -495,6 +816,11 @@ pub fn event_handling(event: Event<()>, control_flow: &mut ControlFlow, egui_sta
                 }
 
                 WindowEvent::MouseInput{state, button, ..} => {
+                    //without a title bar, the overlay is moved by dragging anywhere outside of the egui windows
+                    if gui_state.overlay && button == winit::event::MouseButton::Left && state == ElementState::Pressed && !egui_state.ctx.is_pointer_over_area() {
+                        let _ = egui_state.window.drag_window();
+                    }
+
                     if let Some(pos_in_points_temp) = egui_state.pos_in_points {
                         if let Some(button) = match button {
                                 winit::event::MouseButton::Left => Some(egui::PointerButton::Primary),
@@ -526,18 +852,22 @@ pub fn event_handling(event: Event<()>, control_flow: &mut ControlFlow, egui_sta
                     }
                 }
 
-                WindowEvent::Resized(physical_size) => {
-                    egui_state.glutin_state.gl_surface.resize(
-                        &egui_state.glutin_state.gl_ctx,
-                        std::num::NonZeroU32::new(physical_size.width).unwrap(),
-                        std::num::NonZeroU32::new(physical_size.width).unwrap(),
-                    );
-                    egui_state.window_size = (physical_size.width, physical_size.height);
+                //windows moves minimized windows to -32000, -32000
+                WindowEvent::Moved(position) if position.x > -32000 && position.y > -32000 => {
+                    egui_state.window_pos = Some((position.x, position.y));
+                }
 
-                    unsafe {
-                        gl::Viewport(0, 0, physical_size.width as i32, physical_size.height as i32);
-                        gl::ProgramUniform2f(egui_state.shader, 3, physical_size.width as f32, physical_size.height as f32);
-                    };
+                WindowEvent::Resized(physical_size) => {
+                    //minimizing reports 0x0, keep the last real size for drawing and the config
+                    if let (Some(width), Some(height)) = (std::num::NonZeroU32::new(physical_size.width), std::num::NonZeroU32::new(physical_size.height)) {
+                        egui_state.window_size = (physical_size.width, physical_size.height);
+
+                        //the software renderer resizes its framebuffer when it paints
+                        if let Backend::Gl(glutin_state) = &mut egui_state.backend {
+                            glutin_state.gl_surface.resize(&glutin_state.gl_ctx, width, height);
+                            set_size(glutin_state, egui_state.window_size);
+                        }
+                    }
                 }
 
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
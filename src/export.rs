@@ -0,0 +1,424 @@
+use std::fmt::Write;
+
+use crate::{format_time, rank_rgb};
+
+const BACKGROUND: [u8; 3] = [27, 27, 27];
+const GUIDE: [u8; 3] = [96, 96, 96];
+const MARKER: [u8; 3] = [160, 160, 160];
+const LABEL: [u8; 3] = [160, 160, 160];
+
+//3x5 pixel glyphs for png axis labels, one row per byte with the left pixel in the third bit
+const GLYPHS: [(char, [u8; 5]); 12] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+];
+
+const GLYPH_SCALE: i64 = 2;
+const GLYPH_ADVANCE: f64 = 4.0 * GLYPH_SCALE as f64; //glyph width and a column of space
+
+pub struct Series {
+    pub points: Vec<(f64, f32)>, //(time, rank)
+    pub color: Option<[u8; 3]>, //none colors each segment by its rank
+}
+
+pub struct Options {
+    pub width: u32,
+    pub height: u32,
+    pub steps: u8,
+    pub gradient: Vec<[u8; 3]>, //colors from the lowest to the highest rank, evenly spaced
+    pub segment_colors: bool, //false colors the whole line by its last value
+    pub axes: bool,
+    pub markers: Vec<f64>, //times to draw a vertical line at
+}
+
+//maps (time, rank) to pixel coordinates, leaving room for axis labels when enabled
+struct Frame {
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+    time_min: f64,
+    time_max: f64,
+    rank_max: f64,
+}
+
+impl Frame {
+    fn new(series: &[Series], options: &Options) -> Self {
+        let mut time_min = f64::MAX;
+        let mut time_max = f64::MIN;
+
+        for point in series.iter().flat_map(|series2| &series2.points) {
+            time_min = time_min.min(point.0);
+            time_max = time_max.max(point.0);
+        }
+
+        if time_min > time_max {
+            (time_min, time_max) = (0.0, 1.0);
+        }
+        else if time_min == time_max {
+            time_max = time_min + 1.0;
+        }
+
+        let (left, bottom) = match options.axes {
+            true => (36.0, options.height as f64 - 22.0),
+            false => (4.0, options.height as f64 - 4.0),
+        };
+
+        Self {
+            left,
+            right: options.width as f64 - 4.0,
+            top: 4.0,
+            bottom,
+            time_min,
+            time_max,
+            rank_max: (options.steps - 1).max(1) as f64,
+        }
+    }
+
+    fn x(&self, time: f64) -> f64 {
+        self.left + (time - self.time_min) / (self.time_max - self.time_min) * (self.right - self.left)
+    }
+
+    fn y(&self, rank: f64) -> f64 {
+        self.bottom - rank / self.rank_max * (self.bottom - self.top)
+    }
+
+    //about six evenly spaced times for axis labels
+    fn time_ticks(&self) -> Vec<f64> {
+        let span = self.time_max - self.time_min;
+        let step = [1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0]
+            .into_iter()
+            .find(|step| span / step <= 6.0)
+            .unwrap_or(7200.0);
+
+        let first = (self.time_min / step).ceil() as i64;
+        let last = (self.time_max / step).floor() as i64;
+        (first ..= last).map(|x| x as f64 * step).collect()
+    }
+}
+
+type Segment = ((f64, f32), (f64, f32), [u8; 3]); //(start, end, rgb)
+
+fn segments(series: &[Series], options: &Options) -> Vec<Segment> {
+    let mut segments = Vec::new();
+
+    for series2 in series {
+        let last = series2.points.last().map_or(0.0, |point| point.1);
+
+        for pair in series2.points.windows(2) {
+            let rgb = match (series2.color, options.segment_colors) {
+                (Some(rgb), _) => rgb,
+                (None, true) => rank_rgb(&options.gradient, options.steps, pair[1].1),
+                (None, false) => rank_rgb(&options.gradient, options.steps, last),
+            };
+
+            segments.push((pair[0], pair[1], rgb));
+        }
+    }
+
+    segments
+}
+
+pub fn svg(series: &[Series], options: &Options) -> String {
+    let frame = Frame::new(series, options);
+    let hex = |rgb: [u8; 3]| format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]);
+    let mut out = String::new();
+
+    let _ = writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#, options.width, options.height);
+    let _ = writeln!(out, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(BACKGROUND));
+
+    for rank in [0.0, frame.rank_max] {
+        let _ = writeln!(out, r#"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{}"/>"#, frame.left, frame.right, hex(GUIDE), y = frame.y(rank));
+    }
+
+    for time in &options.markers {
+        let _ = writeln!(out, r#"<line x1="{x:.1}" y1="{:.1}" x2="{x:.1}" y2="{:.1}" stroke="{}" stroke-dasharray="4 3"/>"#, frame.top, frame.bottom, hex(MARKER), x = frame.x(*time));
+    }
+
+    if options.axes {
+        let text = r##"font-family="monospace" font-size="11" fill="#a0a0a0""##;
+
+        for rank in (0 ..= frame.rank_max as u32).step_by(4) {
+            let _ = writeln!(out, r#"<text x="{:.1}" y="{:.1}" text-anchor="end" {text}>{rank}</text>"#, frame.left - 6.0, frame.y(rank as f64) + 4.0);
+        }
+
+        for time in frame.time_ticks() {
+            let _ = writeln!(out, r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" {text}>{}</text>"#, frame.x(time), frame.bottom + 16.0, format_time(time, 0));
+        }
+    }
+
+    for (start, end, rgb) in segments(series, options) {
+        let _ = writeln!(
+            out,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="2" stroke-linecap="round"/>"#,
+            frame.x(start.0), frame.y(start.1 as f64), frame.x(end.0), frame.y(end.1 as f64), hex(rgb),
+        );
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+//the png has the same labels as the svg, drawn with a small pixel font
+pub fn png(series: &[Series], options: &Options) -> Vec<u8> {
+    let frame = Frame::new(series, options);
+    let mut image = Image::new(options.width, options.height);
+
+    for rank in [0.0, frame.rank_max] {
+        image.line((frame.left, frame.y(rank)), (frame.right, frame.y(rank)), 1.0, GUIDE);
+    }
+
+    for time in &options.markers {
+        image.line((frame.x(*time), frame.top), (frame.x(*time), frame.bottom), 1.0, MARKER);
+    }
+
+    if options.axes {
+        for rank in 0 ..= frame.rank_max as u32 {
+            let len = if rank % 4 == 0 { 6.0 } else { 3.0 };
+            image.line((frame.left - len, frame.y(rank as f64)), (frame.left, frame.y(rank as f64)), 1.0, GUIDE);
+
+            if rank % 4 == 0 {
+                let text = rank.to_string();
+                image.text(&text, (frame.left - 8.0 - text_width(&text), frame.y(rank as f64) - 2.5 * GLYPH_SCALE as f64), LABEL);
+            }
+        }
+
+        for time in frame.time_ticks() {
+            image.line((frame.x(time), frame.bottom), (frame.x(time), frame.bottom + 6.0), 1.0, GUIDE);
+
+            let text = format_time(time, 0);
+            image.text(&text, (frame.x(time) - text_width(&text) / 2.0, frame.bottom + 9.0), LABEL);
+        }
+    }
+
+    for (start, end, rgb) in segments(series, options) {
+        image.line((frame.x(start.0), frame.y(start.1 as f64)), (frame.x(end.0), frame.y(end.1 as f64)), 2.0, rgb);
+    }
+
+    image.encode_png()
+}
+
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![BACKGROUND; (width * height) as usize],
+        }
+    }
+
+    //stamps a square of `thickness` pixels along the line, good enough for graph lines
+    fn line(&mut self, start: (f64, f64), end: (f64, f64), thickness: f64, rgb: [u8; 3]) {
+        let len = ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt();
+        let steps = (len * 2.0).ceil().max(1.0) as u32;
+        let size = thickness.round().max(1.0) as i64;
+
+        for step in 0 ..= steps {
+            let t = step as f64 / steps as f64;
+            let x = (start.0 + (end.0 - start.0) * t - thickness / 2.0).round() as i64;
+            let y = (start.1 + (end.1 - start.1) * t - thickness / 2.0).round() as i64;
+
+            for py in y .. y + size {
+                for px in x .. x + size {
+                    self.set(px, py, rgb);
+                }
+            }
+        }
+    }
+
+    //`pos` is the top left corner. characters without a glyph are left as a gap
+    fn text(&mut self, text: &str, pos: (f64, f64), rgb: [u8; 3]) {
+        for (x, c) in text.chars().enumerate() {
+            let rows = match GLYPHS.iter().find(|glyph| glyph.0 == c) {
+                Some(glyph) => glyph.1,
+                None => continue,
+            };
+
+            let left = (pos.0 + x as f64 * GLYPH_ADVANCE).round() as i64;
+            let top = pos.1.round() as i64;
+
+            for (y, row) in rows.iter().enumerate() {
+                for column in 0 .. 3 {
+                    if row & (0b100 >> column) == 0 {
+                        continue;
+                    }
+
+                    for py in 0 .. GLYPH_SCALE {
+                        for px in 0 .. GLYPH_SCALE {
+                            self.set(left + column * GLYPH_SCALE + px, top + y as i64 * GLYPH_SCALE + py, rgb);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, rgb: [u8; 3]) {
+        if (0 .. self.width as i64).contains(&x) && (0 .. self.height as i64).contains(&y) {
+            self.pixels[(y * self.width as i64 + x) as usize] = rgb;
+        }
+    }
+
+    fn encode_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(((self.width * 3 + 1) * self.height) as usize);
+
+        for row in self.pixels.chunks(self.width as usize) {
+            raw.push(0); //no filter
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut ihdr = Vec::new();
+        ihdr.extend(self.width.to_be_bytes());
+        ihdr.extend(self.height.to_be_bytes());
+        ihdr.extend([8, 2, 0, 0, 0]); //8 bit rgb
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        png_chunk(&mut png, b"IHDR", &ihdr);
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn text_width(text: &str) -> f64 {
+    (text.chars().count() as f64 * GLYPH_ADVANCE - GLYPH_SCALE as f64).max(0.0)
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+
+    let crc = crc32(kind.iter().chain(data));
+    png.extend(crc.to_be_bytes());
+}
+
+//zlib stream made of uncompressed deflate blocks. graphs are small, so compression isn't worth the code
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xFFFF).peekable();
+
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        out.push(blocks.peek().is_none() as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
+
+fn crc32<'a>(data: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+
+    for byte in data {
+        crc ^= *byte as u32;
+
+        for _ in 0 .. 8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB88320,
+                _ => crc >> 1,
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //big enough for the image data to span several stored deflate blocks
+    fn options() -> Options {
+        Options {
+            width: 400,
+            height: 300,
+            steps: 16,
+            gradient: vec![[0, 255, 0], [255, 0, 0]],
+            segment_colors: true,
+            axes: true,
+            markers: vec![5.0],
+        }
+    }
+
+    #[test]
+    fn png_decodes() {
+        let series = [Series { points: vec![(0.0, 0.0), (5.0, 15.0), (10.0, 7.0)], color: Some([0, 128, 255]) }];
+        let data = png(&series, &options());
+
+        //the decoder checks the chunk crcs and the zlib adler32 while reading
+        let decoder = ::png::Decoder::new(data.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut rgb = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut rgb).unwrap();
+
+        assert_eq!((info.width, info.height), (400, 300));
+        assert_eq!((info.color_type, info.bit_depth), (::png::ColorType::Rgb, ::png::BitDepth::Eight));
+
+        let pixels: Vec<&[u8]> = rgb.chunks_exact(3).collect();
+        assert_eq!(pixels[0], BACKGROUND);
+        assert!(pixels.contains(&[0, 128, 255].as_slice()));
+        assert!(pixels.contains(&MARKER.as_slice()));
+
+        //rank labels left of the plot
+        assert!(rgb.chunks_exact(400 * 3).any(|row| row[.. 28 * 3].chunks_exact(3).any(|pixel| pixel == LABEL)));
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(format_time(125.4, 0), "2:05");
+        assert_eq!(format_time(125.4, 1), "2:05.4");
+        assert_eq!(format_time(59.96, 1), "1:00.0");
+        assert_eq!(format_time(-3.0, 0), "-0:03");
+    }
+
+    #[test]
+    fn glyphs() {
+        let mut image = Image::new(20, 12);
+        image.text("1:", (0.0, 0.0), LABEL);
+
+        let lit = |x: usize, y: usize| image.pixels[y * 20 + x] == LABEL;
+        assert!(lit(2, 0) && lit(3, 9) && !lit(0, 0)); //the 1
+        assert!(lit(10, 2) && !lit(10, 0)); //the colon
+        assert_eq!(text_width("1:"), 14.0);
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789".iter()), 0xCBF43926);
+        assert_eq!(crc32(b"IEND".iter()), 0xAE426082);
+
+        //adler32 of "Wikipedia" is 0x11E60398
+        assert_eq!(zlib_stored(b"Wikipedia")[.. 2], [0x78, 0x01]);
+        assert_eq!(zlib_stored(b"Wikipedia")[16 ..], 0x11E60398u32.to_be_bytes());
+    }
+}
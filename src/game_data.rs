@@ -1,13 +1,21 @@
-use crate::update::{DataTypes, Rank, SmashTV};
+use crate::{history::History, update::{DataTypes, Rank, SmashTV}};
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum Emulator {
     Bsnes,
     Mame,
 }
 
 impl Emulator {
-    pub fn name_offset(&self, size_of_image: u32, lp_base_of_dll: u64) -> Result<u64, ()> {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "bsnes" => Some(Emulator::Bsnes),
+            "mame" => Some(Emulator::Mame),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name_offset(&self, size_of_image: u32, lp_base_of_dll: u64) -> Result<u64, ()> {
         match self {
             Emulator::Bsnes => Ok(0xB151E8),
 
@@ -19,7 +27,7 @@ impl Emulator {
         }
     }
 
-    pub fn get_mame_version(module_size: u32) -> Result<u16, ()> {
+    pub(crate) fn get_mame_version(module_size: u32) -> Result<u16, ()> {
         match module_size {
             0x129FB000 => Ok(242),
             0x12A82000 => Ok(243),
@@ -28,7 +36,7 @@ impl Emulator {
         }
     }
 
-    pub fn get_mame_name_offset(version: u16) -> u32 {
+    pub(crate) fn get_mame_name_offset(version: u16) -> u32 {
         match version {
             242 => 0x11EC4450,
             243 => 0x11F3C970,
@@ -37,7 +45,7 @@ impl Emulator {
         }
     }
 
-    pub fn mame_game_offset(version: u16, games: Games) -> Option<Vec<u64>> {
+    pub(crate) fn mame_game_offset(version: u16, games: Games) -> Option<Vec<u64>> {
         match version {
             242 => {
                 match games {
@@ -94,11 +102,19 @@ impl Emulator {
     }
 }
 
+pub const SMASH_TV_ENEMY_NAMES: [&str; 16] = [
+    "Empty", "Grunt", "Wall gunner", "Worm",
+    "Red flier", "Snakes", "Snake man", "Laser orb",
+    "Tank", "Red cluster", "Mr. Shrapnel", "Worm (blue)",
+    "Electric orb", "?", "?", "Mine",
+];
+
 pub struct GameData {
     pub id: Games,
     pub data_type: DataTypes,
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum Games {
     Gradius3Snes,
     ParodiusSnes,
@@ -112,6 +128,50 @@ pub enum Games {
 }
 
 impl Games {
+    pub const ALL: [Games; 7] = [
+        Games::Gradius3Snes,
+        Games::ParodiusSnes,
+        Games::SmashTVSnes,
+
+        Games::GhoulsArcade,
+        Games::Gradius2Arcade,
+        Games::Gradius3Arcade,
+        Games::SpangArcade,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Games::Gradius3Snes => "gradius3_snes",
+            Games::ParodiusSnes => "parodius_snes",
+            Games::SmashTVSnes => "smashtv_snes",
+
+            Games::GhoulsArcade => "ghouls_arcade",
+            Games::Gradius2Arcade => "gradius2_arcade",
+            Games::Gradius3Arcade => "gradius3_arcade",
+            Games::SpangArcade => "spang_arcade",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Games> {
+        match id {
+            "gradius3_snes" => Some(Games::Gradius3Snes),
+            "parodius_snes" => Some(Games::ParodiusSnes),
+            "smashtv_snes" => Some(Games::SmashTVSnes),
+
+            "ghouls_arcade" => Some(Games::GhoulsArcade),
+            "gradius2_arcade" => Some(Games::Gradius2Arcade),
+            "gradius3_arcade" => Some(Games::Gradius3Arcade),
+            "spang_arcade" => Some(Games::SpangArcade),
+            _ => None,
+        }
+    }
+
+    //"gradius3" matches both gradius3_snes and gradius3_arcade
+    pub fn matches(&self, name: &str) -> bool {
+        let id = self.id();
+        id == name || id.strip_prefix(name).is_some_and(|rest| rest.starts_with('_'))
+    }
+
     pub fn format_rank(&self, rank: u8) -> u8 {
         match self {
             Games::GhoulsArcade => rank >> 3,
@@ -125,7 +185,7 @@ impl Games {
                 id: Games::Gradius3Snes,
                 data_type: DataTypes::Rank(
                     Rank {
-                        data_points: std::collections::VecDeque::new(),
+                        history: History::new(),
                         offset: 0x0084,
                         steps: 16,
                     }
@@ -136,7 +196,7 @@ impl Games {
                 id: Games::ParodiusSnes,
                 data_type: DataTypes::Rank(
                     Rank {
-                        data_points: std::collections::VecDeque::new(),
+                        history: History::new(),
                         offset: 0x0088,
                         steps: 32,
                     }
@@ -160,7 +220,7 @@ impl Games {
                 id: Games::GhoulsArcade,
                 data_type: DataTypes::Rank(
                     Rank {
-                        data_points: std::collections::VecDeque::new(),
+                        history: History::new(),
                         offset: 0x092A,
                         steps: 16,
                     }
@@ -171,7 +231,7 @@ impl Games {
             //     id: Games::GradiusArcade,
             //     data_type: DataTypes::Rank(
             //         Rank {
-            //             data_points: std::collections::VecDeque::new(),
+            //             history: History::new(),
             //             offset: 0x0102A1 + 0x30b,
             //             steps: 16,
             //         }
@@ -182,7 +242,7 @@ impl Games {
                 id: Games::Gradius2Arcade,
                 data_type: DataTypes::Rank(
                     Rank {
-                        data_points: std::collections::VecDeque::new(),
+                        history: History::new(),
                         offset: 0x041D,
                         steps: 17,
                         //todo: what's the valid range? starting rank on normal difficulty is 2, demo 0.
@@ -195,7 +255,7 @@ impl Games {
                 id: Games::Gradius3Arcade,
                 data_type: DataTypes::Rank(
                     Rank {
-                        data_points: std::collections::VecDeque::new(),
+                        history: History::new(),
                         offset: 0x39C0,
                         steps: 17,
                         //todo: valid range? 0-16?
@@ -208,7 +268,7 @@ impl Games {
                 id: Games::SpangArcade,
                 data_type: DataTypes::Rank(
                     Rank {
-                        data_points: std::collections::VecDeque::new(),
+                        history: History::new(),
                         offset: 0xD2,
                         steps: 32,
                     }
@@ -0,0 +1,96 @@
+use std::io::Write;
+
+use game_data_reader::{log::Log, Reader};
+
+use crate::{alert, args::Args, recorder::{self, Recorder}};
+
+//runs detection and sampling without a window, printing every sample to stdout until stdout is closed
+pub fn run(args: &Args, timer_ticks: i8, mut alerts: Vec<alert::Rule>, mut log: Log) {
+    attach_console();
+    log.echo = true;
+
+    //without --rate, same rate as the window, which updates every `timer_ticks` + 1 ticks of 20ms
+    let interval = match args.rate {
+        Some(rate) => std::time::Duration::from_secs_f32(1.0 / rate),
+        None => std::time::Duration::from_millis((timer_ticks.max(0) as u64 + 1) * 20),
+    };
+
+    let mut reader = Reader::new();
+    reader.filter = args.filter();
+
+    let mut recorder = None;
+
+    loop {
+        let next = std::time::Instant::now() + interval;
+        let attached = reader.current_game.is_some();
+        let samples = reader.poll(&mut log);
+
+        match (&reader.current_game, &args.record) {
+            (Some(_), Some(path)) if !attached => {
+                recorder = match Recorder::append(recorder::Format::from_path(path), path) {
+                    Ok(recorder2) => Some(recorder2),
+
+                    Err(e) => {
+                        log.error(format!("unable to record to {}: {e}", path.display()));
+                        None
+                    }
+                };
+            }
+
+            (None, _) => {
+                alert::reset(&mut alerts);
+
+                if let Some(recorder2) = recorder.take() {
+                    let path = recorder2.path.clone();
+
+                    if let Err(e) = recorder2.stop() {
+                        log.error(format!("unable to save {}: {e}", path.display()));
+                    }
+                }
+            }
+
+            _ => (),
+        }
+
+        if let Some(current_game) = &reader.current_game {
+            alert::check(&mut alerts, &current_game.game.id, &samples, &mut log);
+
+            if let Some(recorder2) = &mut recorder {
+                for sample in &samples {
+                    if let Err(e) = recorder2.write(&current_game.game.id, sample) {
+                        log.error(format!("recording stopped: {e}"));
+                        recorder = None;
+                        break;
+                    }
+                }
+            }
+
+            let mut stdout = std::io::stdout().lock();
+
+            for sample in &samples {
+                let line = match args.json {
+                    false => format!("{:.3} {} {} {}", sample.time, current_game.game.id.id(), sample.name, sample.value),
+                    true => recorder::json_line(&current_game.game.id, sample),
+                };
+
+                if writeln!(stdout, "{line}").is_err() {
+                    return;
+                }
+            }
+
+            if stdout.flush().is_err() {
+                return;
+            }
+        }
+
+        std::thread::sleep(next.saturating_duration_since(std::time::Instant::now()));
+    }
+}
+
+//the binary uses the windows subsystem, so it has no console unless it borrows the one it was started from.
+//redirected handles are kept, so piping into another program works either way
+pub fn attach_console() {
+    unsafe {
+        windows::Win32::System::Console::AttachConsole(windows::Win32::System::Console::ATTACH_PARENT_PROCESS);
+    }
+}
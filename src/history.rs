@@ -0,0 +1,286 @@
+use std::collections::VecDeque;
+
+const CHUNK_LEN: usize = 4096;
+const MAX_CHUNKS: usize = 1024; //~4M samples (32 MB), almost a day at the fastest update rate
+const BLOCK_LEN: usize = 64; //samples per min/max summary, CHUNK_LEN must be a multiple of this
+
+#[derive(Clone, Copy)]
+struct Extremes {
+    min: (usize, f32),
+    max: (usize, f32),
+}
+
+//append-only (time, value) storage. samples keep their index for the whole session, even after the
+//oldest chunks are dropped to keep memory bounded
+pub struct History {
+    chunks: VecDeque<Vec<(f32, f32)>>,
+    blocks: VecDeque<Extremes>,
+    dropped: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            blocks: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    pub fn push(&mut self, time: f64, value: f32) {
+        let index = self.end();
+
+        match self.chunks.back_mut() {
+            Some(chunk) if chunk.len() < CHUNK_LEN => chunk.push((time as f32, value)),
+
+            _ => {
+                if self.chunks.len() == MAX_CHUNKS {
+                    self.chunks.pop_front();
+                    self.blocks.drain(0 .. CHUNK_LEN / BLOCK_LEN);
+                    self.dropped += CHUNK_LEN;
+                }
+
+                let mut chunk = Vec::with_capacity(CHUNK_LEN);
+                chunk.push((time as f32, value));
+                self.chunks.push_back(chunk);
+            }
+        }
+
+        match self.blocks.back_mut() {
+            Some(block) if !index.is_multiple_of(BLOCK_LEN) => {
+                if value < block.min.1 {
+                    block.min = (index, value);
+                }
+
+                if value > block.max.1 {
+                    block.max = (index, value);
+                }
+            }
+
+            _ => self.blocks.push_back(Extremes { min: (index, value), max: (index, value) }),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.blocks.clear();
+        self.dropped = 0;
+    }
+
+    //index of the oldest sample still stored
+    pub fn start(&self) -> usize {
+        self.dropped
+    }
+
+    //index one past the newest sample
+    pub fn end(&self) -> usize {
+        match self.chunks.back() {
+            Some(chunk) => self.dropped + (self.chunks.len() - 1) * CHUNK_LEN + chunk.len(),
+            None => self.dropped,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> (f32, f32) {
+        let x = index - self.dropped;
+        self.chunks[x / CHUNK_LEN][x % CHUNK_LEN]
+    }
+
+    //(time, value) pairs for the stored part of `range`
+    pub fn range(&self, range: std::ops::Range<usize>) -> impl Iterator<Item = (f32, f32)> + '_ {
+        let (start, end) = self.clamp(range);
+        (start .. end).map(move |x| self.get(x))
+    }
+
+    //index of the first sample at or after `time`
+    pub fn index_at(&self, time: f64) -> usize {
+        let time = time as f32;
+        let chunk_nr = self.chunks.partition_point(|chunk| chunk.last().is_some_and(|sample| sample.0 < time));
+
+        match self.chunks.get(chunk_nr) {
+            Some(chunk) => self.dropped + chunk_nr * CHUNK_LEN + chunk.partition_point(|sample| sample.0 < time),
+            None => self.end(),
+        }
+    }
+
+    //reduces `range` to the min and max sample of each bucket, so spikes stay visible while the
+    //number of points to draw stays around 2 * buckets no matter how long the session is
+    pub fn downsample(&self, range: std::ops::Range<usize>, buckets: usize) -> Vec<[f64; 2]> {
+        let (start, end) = self.clamp(range);
+        let len = end - start;
+
+        if buckets == 0 || len <= buckets * 2 {
+            return self.range(start .. end).map(|(x, y)| [x as f64, y as f64]).collect();
+        }
+
+        let mut points = Vec::with_capacity(buckets * 2);
+
+        for bucket in 0 .. buckets {
+            let bucket_start = start + len * bucket / buckets;
+            let bucket_end = start + len * (bucket + 1) / buckets;
+
+            if let Some(extremes) = self.extremes(bucket_start, bucket_end) {
+                let (first, second) = match extremes.min.0 <= extremes.max.0 {
+                    true => (extremes.min, extremes.max),
+                    false => (extremes.max, extremes.min),
+                };
+
+                points.push([self.get(first.0).0 as f64, first.1 as f64]);
+
+                if second.0 != first.0 {
+                    points.push([self.get(second.0).0 as f64, second.1 as f64]);
+                }
+            }
+        }
+
+        points
+    }
+
+    fn clamp(&self, range: std::ops::Range<usize>) -> (usize, usize) {
+        let start = range.start.max(self.start());
+        let end = range.end.min(self.end()).max(start);
+        (start, end)
+    }
+
+    //min and max of start..end. whole blocks use their summary, so only the ends are read sample by sample
+    fn extremes(&self, start: usize, end: usize) -> Option<Extremes> {
+        let mut extremes: Option<Extremes> = None;
+
+        let mut merge = |other: Extremes| {
+            match &mut extremes {
+                Some(extremes2) => {
+                    if other.min.1 < extremes2.min.1 {
+                        extremes2.min = other.min;
+                    }
+
+                    if other.max.1 > extremes2.max.1 {
+                        extremes2.max = other.max;
+                    }
+                }
+
+                None => extremes = Some(other),
+            }
+        };
+
+        let mut x = start;
+
+        while x < end {
+            if x.is_multiple_of(BLOCK_LEN) && x + BLOCK_LEN <= end {
+                merge(self.blocks[(x - self.dropped) / BLOCK_LEN]);
+                x += BLOCK_LEN;
+            }
+            else {
+                let value = self.get(x).1;
+                merge(Extremes { min: (x, value), max: (x, value) });
+                x += 1;
+            }
+        }
+
+        extremes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //noisy values with a spike every so often, time is the sample index
+    fn history(len: usize) -> History {
+        let mut history = History::new();
+        let mut seed = 12345u32;
+
+        for x in 0 .. len {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+
+            let value = match x % 997 {
+                0 => 100.0,
+                _ => (seed >> 16) as f32 % 16.0,
+            };
+
+            history.push(x as f64, value);
+        }
+
+        history
+    }
+
+    //same buckets as `downsample`, but every sample is read instead of the block summaries
+    fn scan(history: &History, start: usize, end: usize, buckets: usize) -> Vec<[f64; 2]> {
+        let len = end - start;
+        let mut points = Vec::new();
+
+        for bucket in 0 .. buckets {
+            let (bucket_start, bucket_end) = (start + len * bucket / buckets, start + len * (bucket + 1) / buckets);
+
+            if bucket_start == bucket_end {
+                continue;
+            }
+
+            let (mut min, mut max) = (bucket_start, bucket_start);
+
+            for x in bucket_start .. bucket_end {
+                if history.get(x).1 < history.get(min).1 {
+                    min = x;
+                }
+
+                if history.get(x).1 > history.get(max).1 {
+                    max = x;
+                }
+            }
+
+            points.push([min.min(max) as f64, history.get(min.min(max)).1 as f64]);
+
+            if min != max {
+                points.push([min.max(max) as f64, history.get(min.max(max)).1 as f64]);
+            }
+        }
+
+        points
+    }
+
+    #[test]
+    fn downsample_across_blocks_and_chunks() {
+        let history = history(CHUNK_LEN * 3 + 100);
+
+        let ranges = [
+            BLOCK_LEN - 3 .. BLOCK_LEN * 5 + 7,
+            CHUNK_LEN - 1 .. CHUNK_LEN + 1000,
+            CHUNK_LEN - BLOCK_LEN - 5 .. CHUNK_LEN * 2 + BLOCK_LEN + 5,
+            1 .. CHUNK_LEN * 3 + 99,
+            0 .. CHUNK_LEN * 3 + 100,
+        ];
+
+        for range in ranges {
+            for buckets in [1, 7, 100, 333] {
+                if range.len() <= buckets * 2 {
+                    continue;
+                }
+
+                let points = history.downsample(range.clone(), buckets);
+                assert_eq!(points, scan(&history, range.start, range.end, buckets), "{range:?} in {buckets} buckets");
+
+                //spikes survive, one per bucket at most
+                let spike = range.clone().any(|x| x % 997 == 0);
+                assert_eq!(points.iter().any(|point| point[1] == 100.0), spike, "{range:?} in {buckets} buckets");
+            }
+        }
+    }
+
+    #[test]
+    fn downsample_more_buckets_than_samples() {
+        let history = history(CHUNK_LEN + 10);
+
+        for (range, buckets) in [(CHUNK_LEN - 20 .. CHUNK_LEN + 20, 40), (CHUNK_LEN - 20 .. CHUNK_LEN + 20, 20), (0 .. 300, 1000), (5 .. 6, 3), (10 .. 10, 5)] {
+            let expected: Vec<[f64; 2]> = history.range(range.clone()).map(|(x, y)| [x as f64, y as f64]).collect();
+            assert_eq!(history.downsample(range.clone(), buckets), expected, "{range:?} in {buckets} buckets");
+        }
+
+        //no buckets returns the samples as they are, past the end is clamped
+        assert_eq!(history.downsample(0 .. CHUNK_LEN + 500, 0).len(), CHUNK_LEN + 10);
+        assert_eq!(history.downsample(CHUNK_LEN .. CHUNK_LEN * 4, 50).len(), 10);
+    }
+}
@@ -0,0 +1,84 @@
+//! reads rank and other values out of emulator memory. the gui binary is one front end for this,
+//! other tools can use `Reader` directly:
+//!
+//! ```no_run
+//! # use game_data_reader::{log::Log, Reader};
+//! let mut log = Log::new("reader.log");
+//! let mut reader = Reader::new();
+//! reader.subscribe(|game, sample| println!("{} {} {}", game.id(), sample.name, sample.value));
+//!
+//! loop {
+//!     reader.poll(&mut log);
+//!     std::thread::sleep(std::time::Duration::from_millis(100));
+//! }
+//! ```
+
+pub mod game_data;
+pub mod history;
+pub mod log;
+pub mod update;
+
+use game_data::Games;
+use log::Log;
+use update::{CurrentGame, Filter, Sample};
+
+type Subscriber = Box<dyn FnMut(&Games, &Sample)>;
+
+//finds a supported game, keeps reading it while it runs and hands every sample to the subscribers
+#[derive(Default)]
+pub struct Reader {
+    pub current_game: Option<CurrentGame>,
+    pub filter: Filter,
+    subscribers: Vec<Subscriber>,
+}
+
+impl Reader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, subscriber: impl FnMut(&Games, &Sample) + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    //searches running emulators for a supported game. returns whether one is attached afterwards
+    pub fn attach(&mut self, log: &mut Log) -> bool {
+        if self.current_game.is_none() {
+            self.current_game = update::find_game(log, &self.filter);
+        }
+
+        self.current_game.is_some()
+    }
+
+    pub fn detach(&mut self) {
+        self.current_game = None;
+    }
+
+    //reads the attached game once, or tries to attach if there is none. detaches when the emulator has closed
+    pub fn poll(&mut self, log: &mut Log) -> Vec<Sample> {
+        let current_game = match &mut self.current_game {
+            Some(current_game) => current_game,
+
+            None => {
+                self.attach(log);
+                return Vec::new();
+            }
+        };
+
+        if !update::check_still_running(current_game) {
+            log.info(format!("detached from {}", current_game.game.id.id()));
+            self.current_game = None;
+            return Vec::new();
+        }
+
+        let samples = update::update(current_game, log);
+
+        for subscriber in &mut self.subscribers {
+            for sample in &samples {
+                subscriber(&current_game.game.id, sample);
+            }
+        }
+
+        samples
+    }
+}
@@ -0,0 +1,232 @@
+use std::{collections::VecDeque, fs::File, io::Write, path::{Path, PathBuf}};
+
+const MAX_ENTRIES: usize = 1000;
+const MAX_FILE_LEN: u64 = 1 << 20; //past this the file is moved to .old.log, replacing the previous one
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+pub struct Entry {
+    pub time: String,
+    pub level: Level,
+    pub message: String,
+    pub repeats: u32, //identical messages in a row are counted instead of added again
+}
+
+pub struct Log {
+    pub entries: VecDeque<Entry>,
+    pub echo: bool, //also print entries to stderr, used when there is no window to show them in
+    path: PathBuf,
+    file: Option<File>,
+    file_len: u64,
+    repeating: bool, //the last entry has repeats that aren't in the file yet
+}
+
+impl Log {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let mut log = Self {
+            entries: VecDeque::new(),
+            echo: false,
+            path: path.as_ref().to_path_buf(),
+            file: None,
+            file_len: 0,
+            repeating: false,
+        };
+
+        log.open();
+        log
+    }
+
+    fn open(&mut self) {
+        self.file_len = std::fs::metadata(&self.path).map(|metadata| metadata.len()).unwrap_or(0);
+
+        if self.file_len > MAX_FILE_LEN {
+            let _ = std::fs::rename(&self.path, self.path.with_extension("old.log"));
+            self.file_len = 0;
+        }
+
+        self.file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path).ok();
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.add(Level::Info, message.into());
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.add(Level::Warning, message.into());
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.add(Level::Error, message.into());
+    }
+
+    fn add(&mut self, level: Level, message: String) {
+        if let Some(last) = self.entries.back_mut() {
+            if last.level == level && last.message == message {
+                last.repeats += 1;
+                self.repeating = true;
+                return;
+            }
+        }
+
+        self.flush();
+
+        let time = date_string(std::time::SystemTime::now());
+        self.write(&time, level, &message);
+
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(Entry { time, level, message, repeats: 0 });
+    }
+
+    //repeats only show up in the window, so the file gets the count once the run is over.
+    //call before exiting in ways that skip dropping the log
+    pub fn flush(&mut self) {
+        let (level, repeats) = match (self.repeating, self.entries.back()) {
+            (true, Some(last)) => (last.level, last.repeats),
+            _ => return,
+        };
+
+        self.repeating = false;
+
+        let time = date_string(std::time::SystemTime::now());
+        self.write(&time, level, &format!("last message repeated, {} times in total", repeats + 1));
+    }
+
+    fn write(&mut self, time: &str, level: Level, message: &str) {
+        let level_name = match level {
+            Level::Info => "info",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        };
+
+        if self.echo {
+            eprintln!("{time} [{level_name}] {message}");
+        }
+
+        if let Some(file) = &mut self.file {
+            let line = format!("{time} [{level_name}] {message}\n");
+
+            match file.write_all(line.as_bytes()) {
+                Ok(()) => self.file_len += line.len() as u64,
+                Err(_) => self.file = None, //stop trying, the window still has the entries
+            }
+
+            if self.file_len > MAX_FILE_LEN {
+                self.file = None;
+                self.open();
+            }
+        }
+    }
+}
+
+impl Drop for Log {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+//utc date as yyyy-mm-dd_hh-mm-ss, used for file names and log entries
+pub fn date_string(time: std::time::SystemTime) -> String {
+    let secs = time.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+    let (days, day_secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    //days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year, month, day, day_secs / 3600, day_secs % 3600 / 60, day_secs % 60,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(path: &std::path::Path) -> Vec<String> {
+        std::fs::read_to_string(path).unwrap().lines().map(|line| line.split_once(' ').unwrap().1.to_string()).collect()
+    }
+
+    #[test]
+    fn repeats_in_file() {
+        let path = std::env::temp_dir().join("game-data-reader-log-test.log");
+        let _ = std::fs::remove_file(&path);
+
+        let mut log = Log::new(&path);
+
+        for _ in 0 .. 3 {
+            log.error("unable to read");
+        }
+
+        log.info("detached");
+        log.info("detached");
+        log.info("attached");
+
+        assert_eq!(log.entries.iter().map(|entry| entry.repeats).collect::<Vec<_>>(), [2, 1, 0]);
+        log.warning("attached");
+        log.warning("attached");
+        log.flush();
+        log.flush();
+
+        //still counted after a flush, then written when the log is dropped
+        log.warning("attached");
+        drop(log);
+
+        assert_eq!(messages(&path), [
+            "[error] unable to read",
+            "[error] last message repeated, 3 times in total",
+            "[info] detached",
+            "[info] last message repeated, 2 times in total",
+            "[info] attached",
+            "[warning] attached",
+            "[warning] last message repeated, 2 times in total",
+            "[warning] last message repeated, 3 times in total",
+        ]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn rotates_file() {
+        let path = std::env::temp_dir().join("game-data-reader-rotate-test.log");
+        let old_path = path.with_extension("old.log");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&old_path);
+
+        std::fs::write(&path, vec![b'-'; MAX_FILE_LEN as usize + 1]).unwrap();
+
+        let mut log = Log::new(&path);
+        assert_eq!(std::fs::metadata(&old_path).unwrap().len(), MAX_FILE_LEN + 1);
+        log.info("started");
+        assert_eq!(messages(&path), ["[info] started"]);
+
+        //also while running
+        let message = "-".repeat(1000);
+
+        for i in 0 .. MAX_FILE_LEN / 1000 + 1 {
+            log.info(format!("{i} {message}"));
+        }
+
+        drop(log);
+        assert!(std::fs::metadata(&path).unwrap().len() < MAX_FILE_LEN);
+        assert_eq!(messages(&old_path)[0], "[info] started");
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&old_path);
+    }
+}
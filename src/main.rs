@@ -1,103 +1,218 @@
 #![windows_subsystem = "windows"]
 
-use egui::{Context, plot::{Plot, Line, LineStyle, PlotPoints}, Color32, RichText};
-use glutin::surface::GlSurface;
-use update::CurrentGame;
+use egui::{Context, plot::{Bar, BarChart, Corner, GridInput, GridMark, Legend, Plot, PlotBounds, Line, LineStyle, PlotPoints}, Color32, RichText};
+use game_data_reader::{game_data, log, update::{self, CurrentGame}, Reader};
 use winit::event_loop::{EventLoop, ControlFlow};
 
+mod alert;
+mod args;
+mod config;
 mod egui_glutin;
-mod game_data;
-mod update;
+mod export;
+mod headless;
+mod recorder;
+mod replay;
+mod server;
+mod software;
+mod stats;
+mod text_output;
+#[cfg(test)]
+mod ui_tests;
 
 pub struct GuiState {
     update_timer: i8,
     timer_ticks: i8,
+    rate_ticks: Option<i8>, //from --rate, used instead of `timer_ticks` until the user changes it and never saved
+
+    windows: Vec<config::WindowSave>, //position and size of every egui window that has been shown
+    overlay: bool,
+    overlay_click_through: bool,
 
     graph: Graph,
-}
+    global_graph: config::GraphSave, //graph settings for games without a profile, `graph` holds the active ones
+    profiles: Vec<config::ProfileSave>,
+    profile_game: Option<game_data::Games>, //game whose settings are in `graph`
 
-#[derive(miniserde::Serialize, miniserde::Deserialize, Debug)]
-struct Save {
-    //main window
-    window_size: (u32, u32),
-    // window_pos: (u32, u32),
+    recorder: Option<recorder::Recorder>,
+    record_format: recorder::Format,
+    record_path: Option<std::path::PathBuf>, //fixed file to record to, set with --record
+    record_status: String,
 
-    timer_ticks: i8,
+    replay: Option<replay::Replay>,
+    replay_path: String,
+    replay_status: String,
+
+    stats_open: bool,
+    death_drop: u8,
+    stats_cache: Option<(game_data::Games, usize, stats::RankStats)>, //(game, history end, stats up to it)
+
+    log: log::Log,
+    log_open: bool,
+
+    config_path: std::path::PathBuf,
+    data_dir: std::path::PathBuf, //recordings and exports go in folders here
+
+    server: Option<server::Server>,
+    server_addr: String,
+
+    text_files: Vec<text_output::TextFile>,
+    text_files_open: bool,
 
-    //rank graph
-	rank_window_pos: (f32, f32),
-	rank_window_width: f32,
-	data_point_len: u16,
-	color_r: (u8, u8),
-    color_g: (u8, u8),
-    color_b: (u8, u8),
-	aspect: f32,
+    alerts: Vec<alert::Rule>,
+    alerts_open: bool,
+    alert_flash: Option<(std::time::Instant, String)>, //last alert that fired, flashed over the window for a moment
 }
 
-impl Default for Save {
-    fn default() -> Self {
+impl GuiState {
+    fn new(save: config::Save, args: &args::Args, mut log: log::Log, config_path: std::path::PathBuf) -> Self {
+        let rate_ticks = args.rate.map(|rate| rate_ticks(rate, &mut log));
+
         Self {
-            window_size: (1024, 768),
+            update_timer: 0,
+            timer_ticks: save.timer_ticks,
+            rate_ticks,
+
+            windows: save.windows.clone(),
+            overlay: save.overlay,
+            overlay_click_through: save.overlay_click_through,
+
+            graph: Graph::new(&save.graph),
+            global_graph: save.graph.clone(),
+            profiles: save.profiles,
+            profile_game: None,
+
+            recorder: None,
+            record_format: match &args.record {
+                Some(path) => recorder::Format::from_path(path),
+                None => recorder::Format::from_extension(&save.record_format),
+            },
+            record_path: args.record.clone(),
+            record_status: String::new(),
+
+            replay: None,
+            replay_path: String::new(),
+            replay_status: String::new(),
+
+            stats_open: save.stats_open,
+            death_drop: save.death_drop,
+            stats_cache: None,
+
+            log,
+            log_open: save.log_open,
+
+            data_dir: config::data_dir(&config_path),
+            config_path,
 
-            timer_ticks: 100,
+            server: None,
+            server_addr: save.server_addr.clone(),
 
-            rank_window_pos: (20.0, 20.0),
-            rank_window_width: 450.0,
-            data_point_len: 240,
-            color_r: (  0, 255),
-            color_g: (255,   0),
-            color_b: (  0,   0),
-            aspect: 3.7,
+            text_files: save.text_files.iter().map(|text_file| {
+                let mut text_file2 = text_output::TextFile::new(&text_file.path, &text_file.template);
+                text_file2.enabled = text_file.enabled;
+                text_file2
+            }).collect(),
+            text_files_open: save.text_files_open,
+
+            alerts: alert_rules(&save.alerts),
+            alerts_open: save.alerts_open,
+            alert_flash: None,
         }
     }
 }
 
+//shaded rank range on the graph, or a threshold line when `from` and `to` are equal
+#[derive(Clone)]
+struct Band {
+    from: f32,
+    to: f32,
+    label: String,
+    color: [u8; 3],
+}
+
 struct Graph {
-    default_window_pos: (f32, f32),
-    default_window_width: f32,
     data_point_len: u16,
     aspect: f32,
-    color_start: [u8; 3],
-    color_end: [u8; 3],
+    gradient: Vec<[u8; 3]>, //rank colors from low to high, at least two
+    segment_colors: bool,
+    bands: Vec<Band>,
+    follow_live: bool,
+    reset_plot: bool,
+    visible_x: (f64, f64), //x range of the rank plot last frame
+    export_axes: bool,
 }
 
 fn main() {
-    let save = if let Ok(str_ser) = std::fs::read_to_string("app.cfg") {
-        miniserde::json::from_str(&str_ser)
-        .expect("Unable to load app.cfg (was created in an older version most likely).")
+    let args = match args::Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+
+        Err(e) => {
+            headless::attach_console();
+            eprintln!("{e}\n\n{}", args::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if args.help {
+        headless::attach_console();
+        println!("{}", args::USAGE);
+        return;
     }
-    else {
-        Save::default()
+
+    let (config_path, path_note) = match args.config.clone() {
+        Some(path) => (path, None),
+        None => config::default_path(),
     };
 
+    let mut log = log::Log::new(config::data_dir(&config_path).join("game-data-reader.log"));
+
+    if let Some(note) = path_note {
+        log.info(note);
+    }
+
+    let (save, config_note) = config::load(&config_path);
+
+    if let Some(note) = config_note {
+        log.warning(note);
+    }
+
+    if let Some(path) = &args.export {
+        headless::attach_console();
+        log.echo = true;
+
+        match export_recording(&args, &save, path) {
+            Ok(()) => log.info(format!("exported {}", path.display())),
+
+            Err(e) => {
+                log.error(e);
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    if args.headless {
+        headless::run(&args, save.timer_ticks, alert_rules(&save.alerts), log);
+        return;
+    }
+
     let el = EventLoop::new();
-    let mut egui_state = egui_glutin::setup_egui_glutin(&el, save.window_size);
+    let mut egui_state = egui_glutin::setup_egui_glutin(&el, save.window_size, save.window_pos, args.software, &mut log);
 
     let mut last_time = std::time::Instant::now();
     let mut frame_time = std::time::Duration::new(0, 0);
 
-    let mut gui_state = GuiState {
-        update_timer: 0,
-        timer_ticks: save.timer_ticks,
-
-        graph: Graph {
-            default_window_pos: save.rank_window_pos,
-            default_window_width: save.rank_window_width,
-            data_point_len: save.data_point_len,
-            aspect: save.aspect,
-            color_start: [save.color_r.0, save.color_g.0, save.color_b.0],
-            color_end: [save.color_r.1, save.color_g.1, save.color_b.1],
-        },
-    };
+    let mut gui_state = GuiState::new(save, &args, log, config_path);
 
     egui_state.ctx.set_pixels_per_point(2.0);
 
-    let mut current_game = None;
+    let mut reader = Reader::new();
+    reader.filter = args.filter();
 
     el.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::WaitUntil(std::time::Instant::now() + std::time::Duration::from_millis(2));
 
-        egui_glutin::event_handling(event, control_flow, &mut egui_state, &gui_state);
+        egui_glutin::event_handling(event, control_flow, &mut egui_state, &mut gui_state);
 
         let current_time = std::time::Instant::now();
         frame_time += current_time - last_time;
@@ -110,138 +225,642 @@ fn main() {
 
             gui_state.update_timer -= 1;
             if gui_state.update_timer < 0 {
-                gui_state.update_timer = gui_state.timer_ticks;
+                gui_state.update_timer = gui_state.rate_ticks.unwrap_or(gui_state.timer_ticks);
+
+                let attached = reader.current_game.is_some();
+                let samples = reader.poll(&mut gui_state.log);
+                switch_profile(&mut gui_state, reader.current_game.as_ref().map(|current_game| current_game.game.id));
 
-                match &mut current_game {
-                    Some(current_game2) => {
-                        if update::check_still_running(current_game2) {
-                            update::update(current_game2);
+                match &reader.current_game {
+                    Some(current_game) => {
+                        if !attached && gui_state.record_path.is_some() && gui_state.recorder.is_none() {
+                            start_recording(&mut gui_state, &current_game.game.id);
                         }
-                        else {
-                            current_game = None;
+
+                        record_samples(&mut gui_state, &current_game.game.id, &samples);
+
+                        let fired = alert::check(&mut gui_state.alerts, &current_game.game.id, &samples, &mut gui_state.log);
+
+                        if let Some(message) = fired.into_iter().last() {
+                            gui_state.alert_flash = Some((std::time::Instant::now(), message));
                         }
                     }
 
-                    None => current_game = update::find_game(),
+                    None => {
+                        stop_recording(&mut gui_state);
+                        alert::reset(&mut gui_state.alerts);
+                    }
                 }
 
-                //todo: kind of a hack. probably pass in guistate to find_game instead?
-                if let Some(current_game2) = &mut current_game {
-                    if let update::DataTypes::Rank(update::Rank{ data_points: data_points2, offset: _, steps: _}) = &mut current_game2.game.data_type {
-                        data_points2.resize(gui_state.graph.data_point_len as usize, 0.0);
-                    }
+                if let Some(server) = &gui_state.server {
+                    server.publish(&reader.current_game);
                 }
+
+                write_text_files(&mut gui_state, &reader.current_game);
             }
 
 
+            if let Some(replay) = &mut gui_state.replay {
+                replay.advance(time as f64 / 1_000_000.0);
+            }
+
             egui_state.ctx.begin_frame(egui_state.raw_input.take());
 
-            create_ui(&mut egui_state.ctx, &mut gui_state, &mut current_game); // add panels, windows and widgets to `egui_ctx` here
+            create_ui(&mut egui_state.ctx, &mut gui_state, &mut reader.current_game); // add panels, windows and widgets to `egui_ctx` here
+            egui_glutin::apply_overlay(&mut egui_state, &gui_state);
 
             let full_output = egui_state.ctx.end_frame();
             let clipped_meshes = egui_state.ctx.tessellate(full_output.shapes); // create triangles to paint
             // my_integration.set_cursor_icon(output.cursor_icon);
-            egui_glutin::update_textures(full_output.textures_delta.set, egui_state.tex);
-            egui_glutin::paint_egui(clipped_meshes, &mut egui_state);
-
-            for &id in &full_output.textures_delta.free {
-                todo!();
-            }
-
-            egui_state.glutin_state.gl_surface.swap_buffers(&egui_state.glutin_state.gl_ctx).unwrap();
+            egui_glutin::paint(&mut egui_state, full_output.textures_delta, clipped_meshes);
         }
     });
 }
 
+//the window updates in ticks of 20ms and can't go beyond what the ticks/update field allows
+fn rate_ticks(rate: f32, log: &mut log::Log) -> i8 {
+    let ticks = (50.0 / rate).round();
+    let ticks2 = ticks.clamp(5.0, 125.0);
+
+    if ticks != ticks2 {
+        log.warning(format!("--rate {rate} is out of range for the window, using {:.2} updates/sec", 50.0 / ticks2));
+    }
+
+    ticks2 as i8
+}
+
 fn create_ui(ctx: &mut Context, gui_state: &mut GuiState, current_game: &mut Option<CurrentGame>) {
     if let Some(current_game2) = current_game {
         match &mut current_game2.game.data_type {
-            update::DataTypes::Rank(rank) => rank_graph(ctx, gui_state, rank),
+            update::DataTypes::Rank(rank) => {
+                rank_graph(ctx, gui_state, &current_game2.game.id, rank);
+
+                if !gui_state.overlay {
+                    stats_window(ctx, gui_state, &current_game2.game.id, rank);
+                }
+            }
+
             update::DataTypes::SmashTV(smash_tv) => smash_tv_display(ctx, gui_state, smash_tv),
         }
     }
 
-    egui::Window::new("Game data reader").show(ctx, |ui| {
+    //the overlay only shows the graphs
+    if gui_state.overlay {
+        if current_game.is_none() {
+            egui::Area::new("overlay_hint").show(ctx, |ui| {
+                ui.label("Searching for supported games... (F10 leaves overlay mode)");
+            });
+        }
+
+        alert_flash(ctx, gui_state);
+        return;
+    }
+
+    replay_window(ctx, gui_state);
+    log_window(ctx, gui_state);
+    text_files_window(ctx, gui_state);
+    alerts_window(ctx, gui_state);
+
+    let response = window(&gui_state.windows, "main", "Game data reader").show(ctx, |ui| {
         ui.horizontal(|ui| {
-            ui.add (
-                egui::DragValue::new(&mut gui_state.timer_ticks)
+            let mut ticks = gui_state.rate_ticks.unwrap_or(gui_state.timer_ticks);
+
+            let response = ui.add (
+                egui::DragValue::new(&mut ticks)
                 .speed(0.23)
                 .clamp_range(5 ..= 125)
                 .prefix("Ticks/update: ")
             );
 
-            ui.label(format!("({:.2} updates/sec)", 50.0 / gui_state.timer_ticks as f32));
+            if response.changed() {
+                gui_state.timer_ticks = ticks;
+                gui_state.rate_ticks = None;
+            }
+
+            ui.label(format!("({:.2} updates/sec)", 50.0 / ticks as f32));
+        });
+
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut gui_state.log_open, "Log");
+            ui.toggle_value(&mut gui_state.text_files_open, "Text files");
+            ui.toggle_value(&mut gui_state.alerts_open, "Alerts");
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut gui_state.overlay, "Overlay mode (F10)")
+            .on_hover_text("Borderless, transparent and always on top, showing only the graphs. Drag the empty space to move it.");
+            ui.checkbox(&mut gui_state.overlay_click_through, "Click-through");
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            match &gui_state.server {
+                Some(server) => {
+                    ui.label(format!("Overlay server on http://{}/", server.addr));
+
+                    if ui.button("Stop").clicked() {
+                        gui_state.log.info(format!("stopped overlay server on {}", server.addr));
+                        gui_state.server = None;
+                    }
+                }
+
+                None => {
+                    ui.add(egui::TextEdit::singleline(&mut gui_state.server_addr).desired_width(120.0));
+
+                    if ui.button("Start overlay server").clicked() {
+                        match server::Server::start(gui_state.server_addr.trim()) {
+                            Ok(server) => {
+                                gui_state.log.info(format!("overlay server listening on {}", server.addr));
+                                gui_state.server = Some(server);
+                            }
+
+                            Err(e) => gui_state.log.error(format!("unable to start overlay server on {}: {e}", gui_state.server_addr)),
+                        }
+                    }
+                }
+            }
         });
 
         if current_game.is_none() {
             ui.label("\nSearching for supported games...");
             ui.label("Once a game has been found, data will be shown automatically!");
         }
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            match &gui_state.recorder {
+                Some(_) => {
+                    if ui.button("Stop recording").clicked() {
+                        stop_recording(gui_state);
+                    }
+
+                    if ui.button("Add marker").clicked() {
+                        if let (Some(recorder), Some(current_game2)) = (&mut gui_state.recorder, &current_game) {
+                            let time = current_game2.start.elapsed().as_secs_f64();
+
+                            if let Err(e) = recorder.marker(&current_game2.game.id, time) {
+                                gui_state.record_status = format!("Unable to add marker: {e}");
+                            }
+                        }
+                    }
+                }
+
+                None => {
+                    ui.add_enabled_ui(current_game.is_some(), |ui| {
+                        let folder = match &gui_state.record_path {
+                            Some(path) => path.display().to_string(),
+                            None => gui_state.data_dir.join("recordings").display().to_string(),
+                        };
+
+                        if ui.button("Start recording").on_hover_text(format!("Records to {folder}")).clicked() {
+                            if let Some(current_game2) = current_game {
+                                start_recording(gui_state, &current_game2.game.id);
+                            }
+                        }
+
+                        ui.radio_value(&mut gui_state.record_format, recorder::Format::Csv, "CSV");
+                        ui.radio_value(&mut gui_state.record_format, recorder::Format::JsonLines, "JSON Lines");
+                    });
+                }
+            }
+        });
+
+        if let Some(recorder) = &gui_state.recorder {
+            ui.label(format!("Recording to {} ({} samples, {} markers)", recorder.path.display(), recorder.sample_count, recorder.marker_count));
+        }
+        else if !gui_state.record_status.is_empty() {
+            ui.label(&gui_state.record_status);
+        }
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut gui_state.replay_path);
+
+            let path = std::path::Path::new(gui_state.replay_path.trim());
+
+            if ui.button("Open recording").clicked() {
+                match replay::Replay::load(path) {
+                    Ok(replay) => {
+                        gui_state.replay = Some(replay);
+                        gui_state.replay_status.clear();
+                    }
+
+                    Err(e) => gui_state.replay_status = format!("Unable to open recording: {e}"),
+                }
+            }
+
+            if let Some(replay) = &mut gui_state.replay {
+                if ui.button("Add to replay").clicked() {
+                    gui_state.replay_status = match replay.add_run(path) {
+                        Ok(()) => String::new(),
+                        Err(e) => format!("Unable to add recording: {e}"),
+                    };
+                }
+            }
+        });
+
+        if !gui_state.replay_status.is_empty() {
+            ui.label(&gui_state.replay_status);
+        }
     });
+
+    remember_window(ctx, &mut gui_state.windows, "main", response);
+}
+
+//egui window placed where it was last time. `id` is what it's saved under
+fn window<'open>(windows: &[config::WindowSave], id: &str, title: impl Into<egui::WidgetText>) -> egui::Window<'open> {
+    let window = egui::Window::new(title).id(egui::Id::new(id));
+
+    match windows.iter().find(|saved| saved.id == id) {
+        Some(saved) => match saved.height {
+            Some(height) => window.default_pos(saved.pos).default_size((saved.width, height)),
+            None => window.default_pos(saved.pos).default_width(saved.width),
+        },
+
+        None => window,
+    }
 }
 
-fn rank_graph(ctx: &mut Context, gui_state: &mut GuiState, rank: &mut update::Rank) {
-    let rect = egui::Rect {
-        min: gui_state.graph.default_window_pos.into(),
-        max: (gui_state.graph.default_window_width, 0.0).into(),
+fn remember_window<R>(ctx: &Context, windows: &mut Vec<config::WindowSave>, id: &str, response: Option<egui::InnerResponse<R>>) {
+    let rect = match response {
+        Some(response2) => response2.response.rect,
+        None => return,
+    };
+
+    //the rect includes the frame's margin and the title bar, default_size doesn't
+    let style = ctx.style();
+    let margin = style.spacing.window_margin;
+    let title_height = ctx.fonts().row_height(&egui::TextStyle::Heading.resolve(&style)).max(style.spacing.interact_size.y) + style.spacing.item_spacing.y;
+
+    let saved = config::WindowSave {
+        id: id.to_string(),
+        pos: (rect.min.x, rect.min.y),
+        width: rect.width() - margin.left - margin.right,
+        height: Some(rect.height() - margin.top - margin.bottom - title_height),
     };
 
-    let response = egui::Window::new("Rank")
+    match windows.iter_mut().find(|saved2| saved2.id == id) {
+        Some(saved2) => *saved2 = saved,
+        None => windows.push(saved),
+    }
+}
+
+fn write_text_files(gui_state: &mut GuiState, current_game: &Option<CurrentGame>) {
+    if !gui_state.text_files.iter().any(|text_file| text_file.enabled) {
+        return;
+    }
+
+    let values = text_output::values(current_game);
+
+    for text_file in gui_state.text_files.iter_mut().filter(|text_file| text_file.enabled) {
+        match text_file.update(&values) {
+            Ok(()) => text_file.error = None,
+
+            Err(e) => {
+                let error = format!("unable to write {}: {e}", text_file.path);
+
+                if text_file.error.as_ref() != Some(&error) {
+                    gui_state.log.error(&error);
+                }
+
+                text_file.error = Some(error);
+            }
+        }
+    }
+}
+
+fn text_files_window(ctx: &mut Context, gui_state: &mut GuiState) {
+    let mut remove = None;
+
+    let response = window(&gui_state.windows, "text_files", "Text files").open(&mut gui_state.text_files_open).show(ctx, |ui| {
+        ui.label(format!("Placeholders: {}\nUse \\n for a line break.", text_output::PLACEHOLDERS));
+        ui.separator();
+
+        for (x, text_file) in gui_state.text_files.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut text_file.enabled, "");
+                ui.add(egui::TextEdit::singleline(&mut text_file.path).desired_width(140.0));
+                ui.add(egui::TextEdit::singleline(&mut text_file.template).desired_width(200.0));
+
+                if ui.button("Remove").clicked() {
+                    remove = Some(x);
+                }
+            });
+
+            if let Some(error) = &text_file.error {
+                ui.colored_label(Color32::LIGHT_RED, error);
+            }
+        }
+
+        if ui.button("Add file").clicked() {
+            gui_state.text_files.push(text_output::TextFile::new("", "{rank}"));
+        }
+    });
+
+    remember_window(ctx, &mut gui_state.windows, "text_files", response);
+
+    if let Some(x) = remove {
+        gui_state.text_files.remove(x);
+    }
+}
+
+fn alert_rules(alerts: &[config::AlertSave]) -> Vec<alert::Rule> {
+    alerts.iter().map(|alert2| {
+        let comparison = alert::Comparison::from_symbol(&alert2.comparison).unwrap_or(alert::Comparison::AtLeast);
+        let mut rule = alert::Rule::new(&alert2.game, &alert2.value, comparison, alert2.level);
+        rule.command = alert2.command.clone();
+        rule.enabled = alert2.enabled;
+        rule
+    }).collect()
+}
+
+fn alerts_window(ctx: &mut Context, gui_state: &mut GuiState) {
+    let mut remove = None;
+
+    let response = window(&gui_state.windows, "alerts", "Alerts").open(&mut gui_state.alerts_open).show(ctx, |ui| {
+        ui.label("Values: rank, active_enemies, enemy_count_0 .. 6\nCommands get GDR_GAME, GDR_NAME and GDR_VALUE.");
+        ui.separator();
+
+        for (x, rule) in gui_state.alerts.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut rule.enabled, "");
+                ui.add(egui::TextEdit::singleline(&mut rule.game).hint_text("any game").desired_width(90.0));
+                ui.add(egui::TextEdit::singleline(&mut rule.value).hint_text("value").desired_width(90.0));
+
+                egui::ComboBox::from_id_source(("alert_comparison", x))
+                .width(40.0)
+                .selected_text(rule.comparison.symbol())
+                .show_ui(ui, |ui| {
+                    for comparison in alert::Comparison::ALL {
+                        ui.selectable_value(&mut rule.comparison, comparison, comparison.symbol());
+                    }
+                });
+
+                ui.add(egui::DragValue::new(&mut rule.level).speed(0.1));
+                ui.add(egui::TextEdit::singleline(&mut rule.command).hint_text("command (optional)").desired_width(140.0));
+
+                if ui.button("Remove").clicked() {
+                    remove = Some(x);
+                }
+            });
+        }
+
+        if ui.button("Add rule").clicked() {
+            gui_state.alerts.push(alert::Rule::new("", "rank", alert::Comparison::AtLeast, 14.0));
+        }
+
+        if let Some((_, message)) = &gui_state.alert_flash {
+            ui.separator();
+            ui.label(format!("Last: {message}"));
+        }
+    });
+
+    remember_window(ctx, &mut gui_state.windows, "alerts", response);
+
+    if let Some(x) = remove {
+        gui_state.alerts.remove(x);
+    }
+
+    alert_flash(ctx, gui_state);
+}
+
+//red frame around the whole window with the message, fading out over a couple of seconds
+fn alert_flash(ctx: &Context, gui_state: &GuiState) {
+    let (time, message) = match &gui_state.alert_flash {
+        Some(flash) => flash,
+        None => return,
+    };
+
+    let fade = 1.0 - time.elapsed().as_secs_f32() / 2.0;
+
+    if fade <= 0.0 {
+        return;
+    }
+
+    let color = Color32::from_rgba_unmultiplied(230, 40, 40, (fade * 255.0) as u8);
+    let rect = ctx.input().screen_rect();
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("alert_flash")));
+
+    painter.rect_stroke(rect.shrink(3.0), 0.0, egui::Stroke::new(6.0, color));
+    painter.text(rect.center_top() + egui::vec2(0.0, 12.0), egui::Align2::CENTER_TOP, message, egui::FontId::proportional(14.0), color);
+}
+
+fn start_recording(gui_state: &mut GuiState, game: &game_data::Games) {
+    let recorder = match &gui_state.record_path {
+        Some(path) => recorder::Recorder::append(gui_state.record_format, path),
+        None => recorder::Recorder::start(gui_state.record_format, game, &gui_state.data_dir.join("recordings")),
+    };
+
+    match recorder {
+        Ok(recorder) => {
+            gui_state.log.info(format!("recording to {}", recorder.path.display()));
+            gui_state.recorder = Some(recorder);
+        }
+
+        Err(e) => {
+            gui_state.record_status = format!("Unable to start recording: {e}");
+            gui_state.log.error(&gui_state.record_status);
+        }
+    }
+}
+
+pub fn stop_recording(gui_state: &mut GuiState) {
+    if let Some(recorder) = gui_state.recorder.take() {
+        let path = recorder.path.clone();
+
+        match recorder.stop() {
+            Ok(()) => {
+                gui_state.record_status = format!("Saved {}", path.display());
+                gui_state.log.info(format!("saved recording {}", path.display()));
+            }
+
+            Err(e) => {
+                gui_state.record_status = format!("Unable to save {}: {e}", path.display());
+                gui_state.log.error(&gui_state.record_status);
+            }
+        }
+    }
+}
+
+fn record_samples(gui_state: &mut GuiState, game: &game_data::Games, samples: &[update::Sample]) {
+    if let Some(recorder) = &mut gui_state.recorder {
+        for sample in samples {
+            if let Err(e) = recorder.write(game, sample) {
+                gui_state.record_status = format!("Recording stopped: {e}");
+                gui_state.log.error(&gui_state.record_status);
+                gui_state.recorder = None;
+                return;
+            }
+        }
+    }
+}
+
+impl Graph {
+    fn new(save: &config::GraphSave) -> Self {
+        let mut graph = Self {
+            data_point_len: 0,
+            aspect: 0.0,
+            gradient: Vec::new(),
+            segment_colors: true,
+            bands: Vec::new(),
+            follow_live: true,
+            reset_plot: false,
+            visible_x: (0.0, 0.0),
+            export_axes: true,
+        };
+
+        graph.apply(save);
+        graph
+    }
+
+    fn apply(&mut self, save: &config::GraphSave) {
+        self.data_point_len = save.data_point_len;
+        self.aspect = save.aspect;
+        self.gradient = save.gradient.iter().filter_map(|hex| config::parse_hex(hex)).collect();
+        self.segment_colors = save.segment_colors;
+
+        self.bands = save.bands.iter().map(|band| Band {
+            from: band.from,
+            to: band.to,
+            label: band.label.clone(),
+            color: config::parse_hex(&band.color).unwrap_or([128; 3]),
+        }).collect();
+
+        if self.gradient.len() < 2 {
+            self.gradient = vec![[0, 255, 0], [255, 0, 0]];
+        }
+    }
+
+    fn to_save(&self) -> config::GraphSave {
+        config::GraphSave {
+            data_point_len: self.data_point_len,
+            gradient: self.gradient.iter().map(|rgb| config::hex(*rgb)).collect(),
+            segment_colors: self.segment_colors,
+            bands: self.bands.iter().map(|band| config::BandSave {
+                from: band.from,
+                to: band.to,
+                label: band.label.clone(),
+                color: config::hex(band.color),
+            }).collect(),
+            aspect: self.aspect,
+        }
+    }
+}
+
+fn profile_index(gui_state: &GuiState, game: Option<game_data::Games>) -> Option<usize> {
+    let id = game?.id();
+    gui_state.profiles.iter().position(|profile| profile.game == id)
+}
+
+//copies the active graph settings back to where they came from, the game's profile or the global settings
+pub fn store_profile(gui_state: &mut GuiState) {
+    let settings = gui_state.graph.to_save();
+
+    match profile_index(gui_state, gui_state.profile_game) {
+        Some(x) => gui_state.profiles[x].graph = settings,
+        None => gui_state.global_graph = settings,
+    }
+}
+
+fn switch_profile(gui_state: &mut GuiState, game: Option<game_data::Games>) {
+    if gui_state.profile_game == game {
+        return;
+    }
+
+    store_profile(gui_state);
+    gui_state.profile_game = game;
+
+    let settings = match profile_index(gui_state, game) {
+        Some(x) => gui_state.profiles[x].graph.clone(),
+        None => gui_state.global_graph.clone(),
+    };
+
+    gui_state.graph.apply(&settings);
+}
+
+fn rank_graph(ctx: &mut Context, gui_state: &mut GuiState, game: &game_data::Games, rank: &mut update::Rank) {
+    let profile = profile_index(gui_state, Some(*game));
+
+    //games with a profile get their own window, so its saved position is used instead of the last game's
+    let window_id = match profile {
+        Some(_) => format!("rank/{}", game.id()),
+        None => "rank".to_string(),
+    };
+
+    let response = window(&gui_state.windows, &window_id, "Rank")
     .collapsible(false)
-    .default_rect(rect)
     .show(ctx, |ui| {
-        let plot = Plot::new("rank")
-        .view_aspect(gui_state.graph.aspect)
-        .allow_boxed_zoom(false)
-        .allow_drag(false)
-        // .y_grid_spacer(spacer) //figure out how this one works
-        .show_axes([false, false]);
+        let end = rank.history.end();
+        let mut plot = base_plot("rank", &gui_state.graph, rank.steps);
 
-        plot
-        .show(ui, |plot_ui| {
-            plot_ui.hline(egui::plot::HLine::new(0.0).color(Color32::DARK_GRAY));
-            plot_ui.hline(egui::plot::HLine::new((rank.steps - 1) as f32).color(Color32::DARK_GRAY));
+        //follow live shows the newest `data_point_len` samples. panning or zooming leaves it and shows the whole session
+        let range = match gui_state.graph.follow_live {
+            true => {
+                let start = end.saturating_sub(gui_state.graph.data_point_len as usize);
+                start .. end
+            }
 
-            let mut rgb = [0; 3];
+            false => {
+                let (min, max) = gui_state.graph.visible_x;
+                rank.history.index_at(min).saturating_sub(1) .. rank.history.index_at(max) + 1
+            }
+        };
 
-            for x in 0 .. 3 {
-                let diff = gui_state.graph.color_end[x] as f32 - gui_state.graph.color_start[x] as f32;
+        if gui_state.graph.reset_plot {
+            plot = plot.reset();
+            gui_state.graph.reset_plot = false;
+        }
 
-                let step = match diff == 0.0 {
-                    false => diff / (rank.steps - 1) as f32,
-                    true => 0.0,
-                };
+        let series = PlotSeries {
+            name: None,
+            points: rank.history.downsample(range.clone(), ui.available_width() as usize),
+            color: None,
+        };
 
-                rgb[x] = (gui_state.graph.color_start[x] as f32 + rank.data_points.back().unwrap() * step).round() as u8;
-            }
+        let (plot_response, bounds) = rank_plot(ui, &gui_state.graph, plot, rank.steps, &[series]);
+        gui_state.graph.visible_x = (bounds.min()[0], bounds.max()[0]);
 
-            plot_ui.line(
-                Line::new(PlotPoints::from_ys_f32(rank.data_points.as_slices().0))
-                .color(Color32::from_rgb(rgb[0], rgb[1], rgb[2]))
-                .style(LineStyle::Solid)
-            )
+        let navigated = plot_response.dragged() || (plot_response.hovered() && {
+            let input = ui.input();
+            input.scroll_delta != egui::Vec2::ZERO || input.zoom_delta() != 1.0
         });
 
-        if ui.button("Clear").clicked() {
-            let len = rank.data_points.len();
-            rank.data_points.clear();
-            rank.data_points.resize(len, 0.0);
+        if navigated {
+            gui_state.graph.follow_live = false;
         }
 
-        ui.collapsing("Advanced", |ui| {
-            let mut count = rank.data_points.len();
+        ui.horizontal(|ui| {
+            if ui.button("Clear").clicked() {
+                rank.history.clear();
+            }
+
+            if ui.checkbox(&mut gui_state.graph.follow_live, "Follow live").changed() && gui_state.graph.follow_live {
+                gui_state.graph.reset_plot = true;
+            }
+
+            ui.toggle_value(&mut gui_state.stats_open, "Statistics");
+
+            if ui.button("Export").on_hover_text(format!("Saves an svg and png to {}", gui_state.data_dir.join("exports").display())).clicked() {
+                let series = export::Series {
+                    points: rank.history.downsample(range, 900).into_iter().map(|point| (point[0], point[1] as f32)).collect(),
+                    color: None,
+                };
+
+                export_graph(gui_state, game, rank.steps, vec![series], Vec::new());
+            }
+        });
 
+        ui.collapsing("Advanced", |ui| {
             ui.add(
-                egui::DragValue::new(&mut count)
+                egui::DragValue::new(&mut gui_state.graph.data_point_len)
                 .speed(0.9)
                 .clamp_range(30 ..= 500)
-                .prefix("data points: ")
+                .prefix("live data points: ")
             );
 
-            if count != rank.data_points.len() {
-                rank.data_points.resize(count, 0.0);
-            }
-
             ui.add(
                 egui::DragValue::new(&mut gui_state.graph.aspect)
                 .speed(0.1)
@@ -250,20 +869,623 @@ fn rank_graph(ctx: &mut Context, gui_state: &mut GuiState, rank: &mut update::Ra
             );
 
             ui.horizontal(|ui| {
-                ui.label("Low/high rank colors: ");
-                ui.color_edit_button_srgb(&mut gui_state.graph.color_start);
-                ui.color_edit_button_srgb(&mut gui_state.graph.color_end);
+                ui.label("Rank colors, low to high: ");
+
+                for rgb in &mut gui_state.graph.gradient {
+                    ui.color_edit_button_srgb(rgb);
+                }
+
+                let len = gui_state.graph.gradient.len();
+
+                //stops are added and removed just before the high color
+                if ui.small_button("+").clicked() {
+                    let rgb = gui_state.graph.gradient[len - 1];
+                    gui_state.graph.gradient.insert(len - 1, rgb);
+                }
+
+                if len > 2 && ui.small_button("-").clicked() {
+                    gui_state.graph.gradient.remove(len - 2);
+                }
             });
+
+            ui.checkbox(&mut gui_state.graph.segment_colors, "Color each part of the line by its rank");
+
+            ui.checkbox(&mut gui_state.graph.export_axes, "Axes in exported images");
+
+            ui.separator();
+            bands_editor(ui, &mut gui_state.graph, rank.steps);
+            ui.separator();
+
+            let mut separate = profile.is_some();
+
+            if ui.checkbox(&mut separate, format!("Separate settings for {}", game.id())).changed() {
+                match profile {
+                    //starts out as a copy of the current settings, the global ones stay as they were
+                    None => gui_state.profiles.push(config::ProfileSave {
+                        game: game.id().to_string(),
+                        graph: gui_state.graph.to_save(),
+                    }),
+
+                    Some(x) => {
+                        gui_state.profiles.remove(x);
+                        gui_state.graph.apply(&gui_state.global_graph.clone());
+                    }
+                }
+            }
+        });
+    });
+
+    remember_window(ctx, &mut gui_state.windows, &window_id, response);
+}
+
+//bands and threshold lines set by the user
+fn bands_editor(ui: &mut egui::Ui, graph: &mut Graph, steps: u8) {
+    let max = (steps - 1) as f32;
+    let mut remove = None;
+
+    for (x, band) in graph.bands.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut band.from).speed(0.1).clamp_range(0.0 ..= max).prefix("from "));
+            ui.add(egui::DragValue::new(&mut band.to).speed(0.1).clamp_range(0.0 ..= max).prefix("to "));
+            ui.color_edit_button_srgb(&mut band.color);
+            ui.add(egui::TextEdit::singleline(&mut band.label).hint_text("label").desired_width(100.0));
+
+            if ui.small_button("Remove").clicked() {
+                remove = Some(x);
+            }
         });
+    }
+
+    if let Some(x) = remove {
+        graph.bands.remove(x);
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("Add band").clicked() {
+            graph.bands.push(Band { from: max - 2.0, to: max, label: String::new(), color: [200, 40, 40] });
+        }
+
+        if ui.button("Add threshold").clicked() {
+            graph.bands.push(Band { from: max / 2.0, to: max / 2.0, label: String::new(), color: [220, 220, 220] });
+        }
+    });
+}
+
+fn stats_window(ctx: &mut Context, gui_state: &mut GuiState, game: &game_data::Games, rank: &update::Rank) {
+    if !gui_state.stats_open {
+        return;
+    }
+
+    let (start, end) = (rank.history.start(), rank.history.end());
+
+    //the cached stats continue where they left off, unless the game or death drop changed or the history
+    //doesn't pick up after the last sample they saw (cleared, or a new session)
+    let continues = match &gui_state.stats_cache {
+        Some((game2, end2, stats2)) if *game2 == *game && stats2.death_drop() == gui_state.death_drop && *end2 <= end => {
+            match stats2.last {
+                None => *end2 == start,
+                Some((time, value)) => *end2 > start && rank.history.get(*end2 - 1) == (time as f32, value),
+            }
+        }
+
+        _ => false,
+    };
+
+    if !continues {
+        gui_state.stats_cache = Some((*game, start, stats::RankStats::new(rank.steps, gui_state.death_drop)));
+    }
+
+    let stats = match &mut gui_state.stats_cache {
+        Some((_, end2, stats2)) => {
+            for (time, value) in rank.history.range(*end2 .. end) {
+                stats2.add(time as f64, value);
+            }
+
+            *end2 = end;
+            &*stats2
+        }
+
+        None => return,
+    };
+
+    let response = window(&gui_state.windows, "stats", "Rank statistics")
+    .open(&mut gui_state.stats_open)
+    .show(ctx, |ui| {
+        let bars = stats.time_at_level.iter().enumerate()
+        .map(|(level, seconds)| Bar::new(level as f64, *seconds).width(0.8).name(format!("rank {level}")))
+        .collect();
+
+        Plot::new("rank histogram")
+        .view_aspect(2.5)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false)
+        .x_axis_formatter(|x, _| format!("{x}"))
+        .y_axis_formatter(|y, _| format_time(y, 1))
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(
+                BarChart::new(bars)
+                .color(rank_color(&gui_state.graph, rank.steps, stats.mean()))
+                .element_formatter(Box::new(|bar, _| format!("{}\n{}", bar.name, format_time(bar.value, 1))))
+            );
+        });
+
+        egui::Grid::new("rank stats").show(ui, |ui| {
+            ui.label("Session length");
+            ui.label(format!("{} ({} samples)", format_time(stats.duration, 1), stats.samples));
+            ui.end_row();
+
+            ui.label("Mean / median rank");
+            ui.label(format!("{:.2} / {}", stats.mean(), stats.median()));
+            ui.end_row();
+
+            ui.label("Max rank");
+            ui.label(format!("{} (reached at {})", stats.max, format_time(stats.time_to_max, 1)));
+            ui.end_row();
+
+            ui.label("Increases / decreases");
+            ui.label(format!("{} / {}", stats.increases, stats.decreases));
+            ui.end_row();
+        });
+
+        ui.separator();
+
+        ui.add(
+            egui::DragValue::new(&mut gui_state.death_drop)
+            .clamp_range(0 ..= 16)
+            .prefix("count drops of at least ")
+            .suffix(" levels as a death (0: off)")
+        );
+
+        ui.label(format!("Deaths: {}", stats.deaths.len()));
+
+        egui::ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+            for (time, rank) in &stats.deaths {
+                ui.label(format!("{}: rank {}", format_time(*time, 1), rank));
+            }
+        });
+    });
+
+    remember_window(ctx, &mut gui_state.windows, "stats", response);
+}
+
+fn log_window(ctx: &mut Context, gui_state: &mut GuiState) {
+    let mut log_window = window(&gui_state.windows, "log", "Log");
+
+    //only for the first time, a saved size wins
+    if !gui_state.windows.iter().any(|saved| saved.id == "log") {
+        log_window = log_window.default_size((400.0, 200.0));
+    }
+
+    let response = log_window
+    .open(&mut gui_state.log_open)
+    .show(ctx, |ui| {
+        egui::ScrollArea::vertical()
+        .auto_shrink([false, false]) //fill the window, so it keeps its size while there are few entries
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            for entry in &gui_state.log.entries {
+                let color = match entry.level {
+                    log::Level::Info => ui.visuals().text_color(),
+                    log::Level::Warning => ui.visuals().warn_fg_color,
+                    log::Level::Error => ui.visuals().error_fg_color,
+                };
+
+                let text = match entry.repeats {
+                    0 => format!("{} {}", entry.time, entry.message),
+                    repeats => format!("{} {} (x{})", entry.time, entry.message, repeats + 1),
+                };
+
+                ui.label(RichText::new(text).monospace().color(color));
+            }
+        });
+    });
+
+    remember_window(ctx, &mut gui_state.windows, "log", response);
+}
+
+struct PlotSeries {
+    name: Option<String>,
+    points: Vec<[f64; 2]>,
+    color: Option<[u8; 3]>, //none uses the low/high rank colors
+}
+
+fn rank_color(graph: &Graph, steps: u8, value: f32) -> Color32 {
+    let rgb = rank_rgb(&graph.gradient, steps, value);
+    Color32::from_rgb(rgb[0], rgb[1], rgb[2])
+}
+
+//color for `value` on a gradient whose stops are spread evenly over the rank range
+fn rank_rgb(gradient: &[[u8; 3]], steps: u8, value: f32) -> [u8; 3] {
+    match gradient.len() {
+        0 => return [255; 3],
+        1 => return gradient[0],
+        _ => (),
+    }
+
+    let t = (value / (steps.max(2) - 1) as f32).clamp(0.0, 1.0) * (gradient.len() - 1) as f32;
+    let index = (t.floor() as usize).min(gradient.len() - 2);
+    let fract = t - index as f32;
+
+    let (start, end) = (gradient[index], gradient[index + 1]);
+    let mut rgb = [0; 3];
+
+    for x in 0 .. 3 {
+        rgb[x] = (start[x] as f32 + (end[x] as f32 - start[x] as f32) * fract).round() as u8;
+    }
+
+    rgb
+}
+
+fn export_options(graph: &Graph, steps: u8, markers: Vec<f64>) -> export::Options {
+    export::Options {
+        width: 900,
+        height: (900.0 / graph.aspect) as u32,
+        steps,
+        gradient: graph.gradient.clone(),
+        segment_colors: graph.segment_colors,
+        axes: graph.export_axes,
+        markers,
+    }
+}
+
+//writes the graph as both svg and png to the exports folder
+fn export_graph(gui_state: &mut GuiState, game: &game_data::Games, steps: u8, series: Vec<export::Series>, markers: Vec<f64>) {
+    let options = export_options(&gui_state.graph, steps, markers);
+
+    let dir = gui_state.data_dir.join("exports");
+    let path = dir.join(format!("{}_{}", game.id(), log::date_string(std::time::SystemTime::now())));
+
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(path.with_extension("svg"), export::svg(&series, &options)))
+        .and_then(|_| std::fs::write(path.with_extension("png"), export::png(&series, &options)));
+
+    match result {
+        Ok(()) => gui_state.log.info(format!("exported {}.svg/.png", path.display())),
+        Err(e) => gui_state.log.error(format!("unable to export {}: {e}", path.display())),
+    }
+}
+
+//--export: draws every run of the recordings in full with the game's graph settings, without opening a window
+fn export_recording(args: &args::Args, save: &config::Save, path: &std::path::Path) -> Result<(), String> {
+    let inputs = match args.replay.is_empty() {
+        true => args.record.iter().cloned().collect(),
+        false => args.replay.clone(),
+    };
+
+    let mut replay = match inputs.first() {
+        Some(input) => replay::Replay::load(input)?,
+        None => return Err("--export needs a recording, given with --replay".to_string()),
+    };
+
+    for input in &inputs[1 ..] {
+        replay.add_run(input)?;
+    }
+
+    let graph = match save.profiles.iter().find(|profile| profile.game == replay.game.id()) {
+        Some(profile) => Graph::new(&profile.graph),
+        None => Graph::new(&save.graph),
+    };
+
+    let series: Vec<export::Series> = replay.runs.iter().map(|run| {
+        let align_time = run.align_time(replay.align);
+
+        export::Series {
+            points: run.samples.iter().map(|sample| (sample.0 - align_time, sample.1)).collect(),
+            color: (!run.gradient).then_some(run.color),
+        }
+    }).collect();
+
+    let markers = replay.runs.first().map_or(Vec::new(), |run| {
+        let align_time = run.align_time(replay.align);
+        run.markers.iter().map(|time| time - align_time).collect()
     });
 
-    let pos = &response.unwrap().response.rect;
-    gui_state.graph.default_window_pos = (pos.min.x, pos.min.y);
-    gui_state.graph.default_window_width = pos.max.x;
+    let options = export_options(&graph, replay.steps, markers);
+    let extension = path.extension().map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+
+    let data = match extension.as_deref() {
+        Some("svg") => export::svg(&series, &options).into_bytes(),
+        Some("png") => export::png(&series, &options),
+        _ => return Err(format!("unable to export {}, only .svg and .png are supported", path.display())),
+    };
+
+    std::fs::write(path, data).map_err(|e| format!("unable to write {}: {e}", path.display()))
+}
+
+//x is seconds since the game was found, y is the rank level
+fn base_plot(id: &str, graph: &Graph, steps: u8) -> Plot {
+    Plot::new(id)
+    .view_aspect(graph.aspect)
+    .y_grid_spacer(rank_grid_spacer(steps))
+    .include_y(steps.saturating_sub(1) as f64 * 1.1) //room for the highest rank's label, which is drawn above its line
+    .x_axis_formatter(|x, range| {
+        //labels are drawn right of their line, one at the right edge would be cut off
+        match x > range.end() - (range.end() - range.start()) * 0.08 {
+            true => String::new(),
+            false => format_time(x, 1),
+        }
+    })
+    .y_axis_formatter(move |y, _| {
+        match y.fract() == 0.0 && (0.0 .. steps as f64).contains(&y) {
+            true => format!("{y}"),
+            false => String::new(),
+        }
+    })
+    .label_formatter(|name, point| {
+        let text = format!("{}\nrank {}", format_time(point.x, 1), point.y.round());
+
+        match name.is_empty() {
+            true => text,
+            false => format!("{name}\n{text}"),
+        }
+    })
+}
+
+//a line for every rank, stronger ones at each quarter of the range and the strongest at the lowest and highest rank,
+//so the highest rank is labelled at any zoom
+fn rank_grid_spacer(steps: u8) -> impl Fn(GridInput) -> Vec<GridMark> {
+    let max = steps.saturating_sub(1).max(1) as f64;
+    let quarters: Vec<f64> = (1..4).map(|quarter| (max * quarter as f64 / 4.0).round()).collect();
+
+    move |input| {
+        let first = input.bounds.0.ceil().max(0.0) as i32;
+        let last = input.bounds.1.floor().min(max) as i32;
+
+        (first ..= last).map(|rank| {
+            let value = rank as f64;
+
+            let step_size = match value == 0.0 || value == max {
+                true => max,
+                false => match quarters.contains(&value) {
+                    true => max / 4.0,
+                    false => 1.0,
+                },
+            };
+
+            GridMark { value, step_size }
+        })
+        .collect()
+    }
+}
+
+//m:ss with `decimals` digits after the seconds, used everywhere times are shown
+fn format_time(seconds: f64, decimals: usize) -> String {
+    let sign = if seconds < 0.0 { "-" } else { "" };
+
+    //rounded first so 59.96 turns into 1:00.0 rather than 0:60.0
+    let scale = 10f64.powi(decimals as i32);
+    let seconds = (seconds.abs() * scale).round() / scale;
+    let minutes = (seconds / 60.0).floor();
+
+    let width = match decimals {
+        0 => 2,
+        _ => decimals + 3,
+    };
+
+    format!("{sign}{minutes}:{:0width$.decimals$}", seconds - minutes * 60.0)
+}
+
+fn rank_plot(ui: &mut egui::Ui, graph: &Graph, mut plot: Plot, steps: u8, series: &[PlotSeries]) -> (egui::Response, PlotBounds) {
+    if series.iter().any(|series2| series2.name.is_some()) {
+        plot = plot.legend(Legend::default().position(Corner::LeftTop));
+    }
+
+    //bands only span the data, spanning the view would keep the plot's automatic bounds from ever shrinking
+    let (x_min, x_max) = series.iter().flat_map(|series2| &series2.points).fold((f64::MAX, f64::MIN), |(min, max), point| (min.min(point[0]), max.max(point[0])));
+
+    let response = plot
+    .show(ui, |plot_ui| {
+        plot_ui.hline(egui::plot::HLine::new(0.0).color(Color32::DARK_GRAY));
+        plot_ui.hline(egui::plot::HLine::new((steps - 1) as f32).color(Color32::DARK_GRAY));
+
+        for band in &graph.bands {
+            let color = Color32::from_rgb(band.color[0], band.color[1], band.color[2]);
+
+            match band.from == band.to {
+                true => plot_ui.hline(egui::plot::HLine::new(band.from).color(color).style(LineStyle::dashed_loose())),
+
+                false if x_min < x_max => {
+                    let (from, to) = (band.from as f64, band.to as f64);
+
+                    plot_ui.polygon(
+                        egui::plot::Polygon::new(PlotPoints::new(vec![[x_min, from], [x_max, from], [x_max, to], [x_min, to]]))
+                        .color(color)
+                        .width(0.0)
+                        .fill_alpha(0.15)
+                    );
+                }
+
+                false => (),
+            }
+
+            if !band.label.is_empty() && x_min <= x_max {
+                let y = band.from.max(band.to) as f64;
+                plot_ui.text(egui::plot::Text::new(egui::plot::PlotPoint::new(x_min, y), &band.label).color(color).anchor(egui::Align2::LEFT_TOP));
+            }
+        }
+
+        for series2 in series {
+            let last = series2.points.last().map_or(0.0, |point| point[1] as f32);
+
+            let parts = match (series2.color, graph.segment_colors) {
+                (Some(rgb), _) => vec![(Color32::from_rgb(rgb[0], rgb[1], rgb[2]), series2.points.clone())],
+                (None, true) => color_segments(&series2.points, |value| rank_color(graph, steps, value as f32)),
+                (None, false) => vec![(rank_color(graph, steps, last), series2.points.clone())],
+            };
+
+            for (color, points) in parts {
+                let mut line = Line::new(PlotPoints::new(points))
+                .color(color)
+                .style(LineStyle::Solid);
+
+                if let Some(name) = &series2.name {
+                    line = line.name(name);
+                }
+
+                plot_ui.line(line);
+            }
+        }
+
+        plot_ui.plot_bounds()
+    });
+
+    (response.response, response.inner)
+}
+
+//splits a line into runs of segments with the same color, each segment colored by the value it ends at.
+//neighbouring runs share a point so the line stays connected
+fn color_segments(points: &[[f64; 2]], color: impl Fn(f64) -> Color32) -> Vec<(Color32, Vec<[f64; 2]>)> {
+    let mut parts: Vec<(Color32, Vec<[f64; 2]>)> = Vec::new();
+
+    for pair in points.windows(2) {
+        let color2 = color(pair[1][1]);
+
+        match parts.last_mut() {
+            Some((last_color, last_points)) if *last_color == color2 => last_points.push(pair[1]),
+            _ => parts.push((color2, vec![pair[0], pair[1]])),
+        }
+    }
+
+    if parts.is_empty() && !points.is_empty() {
+        parts.push((color(points[0][1]), points.to_vec()));
+    }
+
+    parts
+}
+
+fn replay_window(ctx: &mut Context, gui_state: &mut GuiState) {
+    let mut open = true;
+    let mut export = false;
+
+    if let Some(replay) = &mut gui_state.replay {
+        let response = window(&gui_state.windows, "replay", format!("Replay - {}", replay.game.id()))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            let len = gui_state.graph.data_point_len as usize;
+
+            let series: Vec<PlotSeries> = replay.runs.iter().map(|run| {
+                PlotSeries {
+                    name: (replay.runs.len() > 1).then(|| run.name.clone()),
+                    points: run.visible_points(replay.position, replay.align, len),
+                    color: (!run.gradient).then_some(run.color),
+                }
+            }).collect();
+
+            let plot = base_plot("replay", &gui_state.graph, replay.steps)
+            .allow_boxed_zoom(false)
+            .allow_drag(false);
+
+            rank_plot(ui, &gui_state.graph, plot, replay.steps, &series);
+
+            ui.horizontal(|ui| {
+                let label = match replay.playing {
+                    true => "Pause",
+                    false => "Play",
+                };
+
+                let (start, end) = replay.range();
+
+                if ui.button(label).clicked() {
+                    if !replay.playing && replay.position >= end {
+                        replay.position = start;
+                    }
+
+                    replay.playing = !replay.playing;
+                }
+
+                ui.add(
+                    egui::DragValue::new(&mut replay.speed)
+                    .speed(0.05)
+                    .clamp_range(0.1 ..= 16.0)
+                    .prefix("speed: ")
+                    .suffix("x")
+                );
+            });
+
+            let (start, end) = replay.range();
+            ui.add(
+                egui::Slider::new(&mut replay.position, start ..= end)
+                .show_value(false)
+            );
+
+            ui.horizontal(|ui| {
+                ui.label(format!("{} / {}", format_time(replay.position, 1), format_time(end, 1)));
+                export = ui.button("Export").on_hover_text(format!("Saves an svg and png to {}", gui_state.data_dir.join("exports").display())).clicked();
+            });
+
+            ui.collapsing("Runs", |ui| {
+                let align_text = |align| match align {
+                    replay::Align::Start => "start of recording".to_string(),
+                    replay::Align::Marker(nr) => format!("marker {}", nr + 1),
+                };
+
+                egui::ComboBox::from_label("Align by")
+                .selected_text(align_text(replay.align))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut replay.align, replay::Align::Start, align_text(replay::Align::Start));
+
+                    for nr in 0 .. replay.marker_count() {
+                        ui.selectable_value(&mut replay.align, replay::Align::Marker(nr), align_text(replay::Align::Marker(nr)));
+                    }
+                });
+
+                let mut remove = None;
+
+                for (x, run) in replay.runs.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add_enabled_ui(!run.gradient, |ui| ui.color_edit_button_srgb(&mut run.color));
+                        ui.checkbox(&mut run.gradient, "rank colors");
+                        ui.label(&run.name);
+
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(x);
+                        }
+                    });
+                }
+
+                if let Some(x) = remove {
+                    replay.runs.remove(x);
+                }
+            });
+        });
+
+        remember_window(ctx, &mut gui_state.windows, "replay", response);
+
+        if replay.runs.is_empty() {
+            open = false;
+        }
+    }
+
+    if export {
+        if let Some(replay) = &gui_state.replay {
+            let len = gui_state.graph.data_point_len as usize;
+
+            let series = replay.runs.iter().map(|run| {
+                export::Series {
+                    points: run.visible_points(replay.position, replay.align, len).into_iter().map(|point| (point[0], point[1] as f32)).collect(),
+                    color: (!run.gradient).then_some(run.color),
+                }
+            }).collect();
+
+            let markers = replay.runs.first().map_or(Vec::new(), |run| {
+                let align_time = run.align_time(replay.align);
+                run.markers.iter().map(|time| time - align_time).collect()
+            });
+
+            let (game, steps) = (replay.game, replay.steps);
+            export_graph(gui_state, &game, steps, series, markers);
+        }
+    }
+
+    if !open {
+        gui_state.replay = None;
+    }
 }
 
 fn smash_tv_display(ctx: &mut Context, gui_state: &mut GuiState, smash_tv: &update::SmashTV) {
-    egui::Window::new("Smash TV")
+    let response = window(&gui_state.windows, "smash_tv", "Smash TV")
     .collapsible(false)
     .show(ctx, |ui| {
         ui.label(
@@ -273,18 +1495,11 @@ fn smash_tv_display(ctx: &mut Context, gui_state: &mut GuiState, smash_tv: &upda
 
         for x in 0 .. 7 {
             if smash_tv.enemy_type[x] != 0 {
-                let names = [
-                    "Empty", "Grunt", "Wall gunner", "Worm",
-                    "Red flier", "Snakes", "Snake man", "Laser orb",
-                    "Tank", "Red cluster", "Mr. Shrapnel", "Worm (blue)",
-                    "Electric orb", "?", "?", "Mine",
-                ];
-
                 ui.label(
                     RichText::new(
                         format!(
                             "{:12} | {:>5} | {:.1} | {}",
-                            names[smash_tv.enemy_type[x] as usize & 0x0F],
+                            game_data::SMASH_TV_ENEMY_NAMES[smash_tv.enemy_type[x] as usize & 0x0F],
                             smash_tv.enemy_count[x],
                             smash_tv.spawn_timer[x] as f32 / 60.0,
                             smash_tv.active_enemies[0] as u16 + smash_tv.enemy_count[x],
@@ -295,4 +1510,6 @@ fn smash_tv_display(ctx: &mut Context, gui_state: &mut GuiState, smash_tv: &upda
             }
         }
     });
+
+    remember_window(ctx, &mut gui_state.windows, "smash_tv", response);
 }
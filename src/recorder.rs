@@ -0,0 +1,146 @@
+use std::{fs::File, io::{BufWriter, Write}, path::{Path, PathBuf}};
+
+use game_data_reader::{game_data::Games, log::date_string, update::Sample};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::JsonLines => "jsonl",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Self {
+        match extension {
+            "jsonl" | "json" => Format::JsonLines,
+            _ => Format::Csv,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Self {
+        Self::from_extension(path.extension().and_then(|ext| ext.to_str()).unwrap_or(""))
+    }
+}
+
+#[derive(miniserde::Serialize, miniserde::Deserialize)]
+pub struct JsonSample {
+    pub time: f64,
+    pub frame: Option<u64>,
+    pub game: String,
+    pub name: String,
+    pub raw: u32,
+    pub value: f32,
+}
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+    format: Format,
+    pub path: PathBuf,
+    pub sample_count: u64,
+    pub marker_count: u32,
+}
+
+impl Recorder {
+    //a new file named after the game and the time in `dir`
+    pub fn start(format: Format, game: &Games, dir: &Path) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+
+        let path = dir
+            .join(format!("{}_{}.{}", game.id(), date_string(std::time::SystemTime::now()), format.extension()));
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        if format == Format::Csv {
+            writeln!(writer, "time,frame,game,name,raw,value")?;
+        }
+
+        Ok(Self {
+            writer,
+            format,
+            path,
+            sample_count: 0,
+            marker_count: 0,
+        })
+    }
+
+    //records into a fixed file instead, adding to it if it exists so one file can hold several sessions
+    pub fn append(format: Format, path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let empty = file.metadata()?.len() == 0;
+        let mut writer = BufWriter::new(file);
+
+        if format == Format::Csv && empty {
+            writeln!(writer, "time,frame,game,name,raw,value")?;
+        }
+
+        Ok(Self {
+            writer,
+            format,
+            path: path.to_path_buf(),
+            sample_count: 0,
+            marker_count: 0,
+        })
+    }
+
+    pub fn write(&mut self, game: &Games, sample: &Sample) -> std::io::Result<()> {
+        match self.format {
+            Format::Csv => {
+                let frame = match sample.frame {
+                    Some(frame) => frame.to_string(),
+                    None => String::new(),
+                };
+
+                writeln!(self.writer, "{:.3},{},{},{},{},{}", sample.time, frame, game.id(), sample.name, sample.raw, sample.value)?;
+            }
+
+            Format::JsonLines => writeln!(self.writer, "{}", json_line(game, sample))?,
+        }
+
+        self.sample_count += 1;
+        Ok(())
+    }
+
+    //written as its own sample so recordings can be lined up on it later, e.g. at the start of a stage
+    pub fn marker(&mut self, game: &Games, time: f64) -> std::io::Result<()> {
+        let sample = Sample {
+            time,
+            frame: None,
+            name: "marker".to_string(),
+            raw: self.marker_count,
+            value: self.marker_count as f32,
+        };
+
+        self.write(game, &sample)?;
+        self.marker_count += 1;
+        Ok(())
+    }
+
+    pub fn stop(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+pub fn json_line(game: &Games, sample: &Sample) -> String {
+    let json_sample = JsonSample {
+        time: sample.time,
+        frame: sample.frame,
+        game: game.id().to_string(),
+        name: sample.name.clone(),
+        raw: sample.raw,
+        value: sample.value,
+    };
+
+    miniserde::json::to_string(&json_sample)
+}
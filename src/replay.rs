@@ -0,0 +1,321 @@
+use std::path::Path;
+
+use game_data_reader::{game_data::Games, update::DataTypes};
+
+use crate::recorder::{Format, JsonSample};
+
+const RUN_COLORS: [[u8; 3]; 6] = [
+    [ 80, 160, 255],
+    [255, 160,  40],
+    [200,  90, 255],
+    [ 60, 220, 200],
+    [255, 100, 150],
+    [220, 220,  80],
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Align {
+    Start,
+    Marker(usize),
+}
+
+pub struct Run {
+    pub name: String, //file name, numbered when the file holds several sessions
+    pub samples: Vec<(f64, f32)>, //(time, rank)
+    pub markers: Vec<f64>,
+
+    pub color: [u8; 3],
+    pub gradient: bool, //use the graph's low/high rank colors instead of `color`
+}
+
+impl Run {
+    //time that lines up with position 0
+    pub fn align_time(&self, align: Align) -> f64 {
+        let start = self.samples.first().map_or(0.0, |sample| sample.0);
+
+        match align {
+            Align::Start => start,
+            Align::Marker(nr) => self.markers.get(nr).copied().unwrap_or(start),
+        }
+    }
+
+    //the last `len` samples up to the playback position as (aligned time, rank)
+    pub fn visible_points(&self, position: f64, align: Align, len: usize) -> Vec<[f64; 2]> {
+        let align_time = self.align_time(align);
+        let end = self.samples.partition_point(|sample| sample.0 - align_time <= position);
+
+        self.samples[end.saturating_sub(len) .. end].iter()
+        .map(|sample| [sample.0 - align_time, sample.1 as f64])
+        .collect()
+    }
+}
+
+pub struct Replay {
+    pub game: Games,
+    pub steps: u8,
+    pub runs: Vec<Run>,
+    pub align: Align,
+
+    pub position: f64,
+    pub playing: bool,
+    pub speed: f32,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let (game, runs) = load_runs(path, 0)?;
+
+        let steps = match game.game_info().data_type {
+            DataTypes::Rank(rank) => rank.steps,
+            _ => return Err(format!("{} has no rank", game.id())),
+        };
+
+        Ok(Self {
+            game,
+            steps,
+            runs,
+            align: Align::Start,
+
+            position: 0.0,
+            playing: false,
+            speed: 1.0,
+        })
+    }
+
+    pub fn add_run(&mut self, path: &Path) -> Result<(), String> {
+        let (game, runs) = load_runs(path, self.runs.len())?;
+
+        if game.id() != self.game.id() {
+            return Err(format!("{} is a recording of {}, not {}", path.display(), game.id(), self.game.id()));
+        }
+
+        self.runs.extend(runs);
+        Ok(())
+    }
+
+    //the earliest and latest position covered by any run
+    pub fn range(&self) -> (f64, f64) {
+        let mut range = (0.0f64, 0.0f64);
+
+        for run in &self.runs {
+            let align_time = run.align_time(self.align);
+
+            if let (Some(first), Some(last)) = (run.samples.first(), run.samples.last()) {
+                range.0 = range.0.min(first.0 - align_time);
+                range.1 = range.1.max(last.0 - align_time);
+            }
+        }
+
+        range
+    }
+
+    pub fn marker_count(&self) -> usize {
+        self.runs.iter().map(|run| run.markers.len()).max().unwrap_or(0)
+    }
+
+    pub fn advance(&mut self, seconds: f64) {
+        if self.playing {
+            self.position += seconds * self.speed as f64;
+
+            let end = self.range().1;
+            if self.position >= end {
+                self.position = end;
+                self.playing = false;
+            }
+        }
+    }
+}
+
+//samples of one attach. a file recorded with --record holds one after another, each starting again at time 0
+#[derive(Default)]
+struct Session {
+    game: Option<String>,
+    samples: Vec<(f64, f32)>,
+    markers: Vec<f64>,
+    last_time: f64,
+}
+
+//one run per session of the recording's game, sessions of other games in the same file are left out
+fn load_runs(path: &Path, index: usize) -> Result<(Games, Vec<Run>), String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let json = Format::from_path(path) == Format::JsonLines; //same rule as the recorder, which also writes .json as json lines
+    let mut sessions: Vec<Session> = Vec::new();
+
+    for (line_nr, line) in text.lines().enumerate() {
+        if line.is_empty() || (!json && line_nr == 0) {
+            continue; //skip csv header
+        }
+
+        let sample = match json {
+            true => miniserde::json::from_str::<JsonSample>(line).map_err(|_| format!("invalid sample on line {}", line_nr + 1))?,
+            false => parse_csv_line(line).ok_or(format!("invalid sample on line {}", line_nr + 1))?,
+        };
+
+        if sessions.last().is_none_or(|session| sample.time < session.last_time) {
+            sessions.push(Session::default());
+        }
+
+        let session = sessions.last_mut().unwrap();
+        session.last_time = sample.time;
+
+        match sample.name.as_str() {
+            "rank" => {
+                session.game.get_or_insert(sample.game);
+                session.samples.push((sample.time, sample.value));
+            }
+
+            "marker" => session.markers.push(sample.time),
+            _ => (),
+        }
+    }
+
+    let game_id = sessions.iter().find_map(|session| session.game.clone()).ok_or("no rank samples in recording")?;
+    let game = Games::from_id(&game_id).ok_or(format!("unknown game: {game_id}"))?;
+
+    sessions.retain(|session| session.game.as_ref() == Some(&game_id));
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let count = sessions.len();
+
+    let runs = sessions.into_iter().enumerate().map(|(x, session)| {
+        Run {
+            name: match count {
+                1 => stem.clone(),
+                _ => format!("{stem} #{}", x + 1),
+            },
+            samples: session.samples,
+            markers: session.markers,
+
+            color: RUN_COLORS[(index + x) % RUN_COLORS.len()],
+            gradient: index + x == 0,
+        }
+    }).collect();
+
+    Ok((game, runs))
+}
+
+fn parse_csv_line(line: &str) -> Option<JsonSample> {
+    let fields: Vec<&str> = line.split(',').collect();
+
+    if fields.len() != 6 {
+        return None;
+    }
+
+    Some(JsonSample {
+        time: fields[0].parse().ok()?,
+        frame: fields[1].parse().ok(),
+        game: fields[2].to_string(),
+        name: fields[3].to_string(),
+        raw: fields[4].parse().ok()?,
+        value: fields[5].parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use game_data_reader::update::Sample;
+
+    use super::*;
+    use crate::recorder::Recorder;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("game-data-reader-replay-tests");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join(name);
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    //one session, like a single attach while recording
+    fn record(path: &Path, game: Games) {
+        let mut recorder = Recorder::append(Format::from_path(path), path).unwrap();
+
+        for x in 0 .. 20 {
+            let sample = Sample { time: x as f64 * 0.25, frame: (x % 2 == 0).then_some(x as u64 * 15), name: "rank".to_string(), raw: x, value: (x % 16) as f32 };
+            recorder.write(&game, &sample).unwrap();
+
+            if x == 8 {
+                recorder.marker(&game, sample.time).unwrap();
+            }
+        }
+
+        recorder.stop().unwrap();
+    }
+
+    #[test]
+    fn load_recordings() {
+        for name in ["round_trip.csv", "round_trip.jsonl", "round_trip.json"] {
+            let path = temp_path(name);
+            record(&path, Games::Gradius3Snes);
+
+            let replay = Replay::load(&path).unwrap();
+            let run = &replay.runs[0];
+
+            assert_eq!(replay.game.id(), Games::Gradius3Snes.id(), "{name}");
+            assert_eq!(replay.steps, 16, "{name}");
+            assert_eq!(run.samples.len(), 20, "{name}");
+            assert_eq!(run.samples[5], (1.25, 5.0), "{name}");
+            assert_eq!(run.markers, [2.0], "{name}");
+
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn sessions() {
+        let path = temp_path("sessions.csv");
+        record(&path, Games::Gradius3Snes);
+        record(&path, Games::ParodiusSnes);
+        record(&path, Games::Gradius3Snes);
+
+        let replay = Replay::load(&path).unwrap();
+
+        assert_eq!(replay.game.id(), Games::Gradius3Snes.id());
+        assert_eq!(replay.runs.iter().map(|run| run.name.as_str()).collect::<Vec<_>>(), ["sessions #1", "sessions #2"]);
+
+        for run in &replay.runs {
+            assert_eq!(run.samples.len(), 20);
+            assert_eq!(run.markers, [2.0]);
+            assert!(run.samples.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn csv_lines() {
+        let sample = parse_csv_line("1.500,90,gradius3_snes,rank,7,7").unwrap();
+        assert_eq!((sample.time, sample.frame, sample.game.as_str(), sample.name.as_str(), sample.raw, sample.value), (1.5, Some(90), "gradius3_snes", "rank", 7, 7.0));
+
+        //the frame is optional, everything else isn't
+        assert_eq!(parse_csv_line("1.500,,gradius3_snes,rank,7,7").unwrap().frame, None);
+        assert!(parse_csv_line("").is_none());
+        assert!(parse_csv_line("1.500,90,gradius3_snes,rank,7").is_none());
+        assert!(parse_csv_line("1.500,90,gradius3_snes,rank,7,7,7").is_none());
+        assert!(parse_csv_line("soon,90,gradius3_snes,rank,7,7").is_none());
+        assert!(parse_csv_line("1.500,90,gradius3_snes,rank,-1,7").is_none());
+        assert!(parse_csv_line("1.500,90,gradius3_snes,rank,7,high").is_none());
+    }
+
+    #[test]
+    fn malformed_recordings() {
+        let cases = [
+            ("bad_line.csv", "time,frame,game,name,raw,value\n0.000,,gradius3_snes,rank,1,1\n0.250,gradius3_snes,rank,2,2\n", "invalid sample on line 3"),
+            ("bad_line.jsonl", "{\"time\":0.0,\"frame\":null,\"game\":\"gradius3_snes\",\"name\":\"rank\",\"raw\":1,\"value\":1.0}\n{\"time\":0.25,\n", "invalid sample on line 2"),
+            ("no_rank.csv", "time,frame,game,name,raw,value\n0.000,,smash_tv_snes,active_enemies,3,3\n", "no rank samples in recording"),
+            ("unknown_game.csv", "time,frame,game,name,raw,value\n0.000,,not_a_game,rank,1,1\n", "unknown game: not_a_game"),
+        ];
+
+        for (name, text, error) in cases {
+            let path = temp_path(name);
+            std::fs::write(&path, text).unwrap();
+
+            assert_eq!(Replay::load(&path).err().as_deref(), Some(error), "{name}");
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
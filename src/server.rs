@@ -0,0 +1,452 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}, mpsc::{self, SyncSender, TrySendError}},
+};
+
+use game_data_reader::{game_data, update::{CurrentGame, DataTypes}};
+
+const OVERLAY: &str = include_str!("overlay.html");
+const HISTORY_LEN: usize = 240; //samples sent with /state so the overlay starts with a filled graph
+const QUEUE_LEN: usize = 8; //frames waiting for a slow client, newer ones are dropped once it is full
+const MAX_PAYLOAD: u64 = 0x10000; //client frames are only control frames, anything bigger closes the connection
+
+//frames are written by one thread per client, so a stalled client never holds up `publish`
+type Frame = Arc<Vec<u8>>;
+
+#[derive(miniserde::Serialize)]
+struct State {
+    game: Option<String>,
+    time: f64,
+    steps: Option<u8>,
+    rank: Option<f32>,
+    history: Vec<(f32, f32)>,
+    waves: Vec<Wave>,
+    active_enemies: Option<u8>,
+}
+
+#[derive(miniserde::Serialize)]
+struct Wave {
+    name: String,
+    count: u16,
+    spawn_timer: f32,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: String,
+    clients: Vec<(u64, SyncSender<Frame>)>, //(id, queue of the client's writer thread)
+    next_id: u64,
+}
+
+//serves the overlay page on /, the current state as json on /state and pushes every update to websocket clients on /ws
+pub struct Server {
+    pub addr: String,
+    shared: Arc<Mutex<Shared>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Server {
+    pub fn start(addr: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?; //so the thread can notice when the server is stopped
+
+        let shared = Arc::new(Mutex::new(Shared { state: state_json(&None, false), ..Default::default() }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let (shared2, running2) = (shared.clone(), running.clone());
+        std::thread::spawn(move || {
+            while running2.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let (shared3, running3) = (shared2.clone(), running2.clone());
+                        std::thread::spawn(move || handle_connection(stream, shared3, running3));
+                    }
+
+                    Err(_) => std::thread::sleep(std::time::Duration::from_millis(50)),
+                }
+            }
+        });
+
+        Ok(Self {
+            addr: addr.to_string(),
+            shared,
+            running,
+        })
+    }
+
+    pub fn publish(&self, current_game: &Option<CurrentGame>) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.state = state_json(current_game, true);
+
+        let frame = Arc::new(websocket_frame(OPCODE_TEXT, state_json(current_game, false).as_bytes()));
+
+        shared.clients.retain(|(_, client)| match client.try_send(frame.clone()) {
+            Ok(()) | Err(TrySendError::Full(_)) => true,
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        self.shared.lock().unwrap().clients.clear();
+    }
+}
+
+fn state_json(current_game: &Option<CurrentGame>, with_history: bool) -> String {
+    let mut state = State {
+        game: None,
+        time: 0.0,
+        steps: None,
+        rank: None,
+        history: Vec::new(),
+        waves: Vec::new(),
+        active_enemies: None,
+    };
+
+    if let Some(current_game2) = current_game {
+        state.game = Some(current_game2.game.id.id().to_string());
+        state.time = current_game2.start.elapsed().as_secs_f64();
+
+        match &current_game2.game.data_type {
+            DataTypes::Rank(rank) => {
+                let end = rank.history.end();
+
+                state.steps = Some(rank.steps);
+                state.rank = (end > rank.history.start()).then(|| rank.history.get(end - 1).1);
+
+                if with_history {
+                    state.history = rank.history.range(end.saturating_sub(HISTORY_LEN) .. end).collect();
+                }
+            }
+
+            DataTypes::SmashTV(smash_tv) => {
+                state.active_enemies = Some(smash_tv.active_enemies[0]);
+
+                for x in 0 .. 7 {
+                    if smash_tv.enemy_type[x] != 0 {
+                        state.waves.push(Wave {
+                            name: game_data::SMASH_TV_ENEMY_NAMES[smash_tv.enemy_type[x] as usize & 0x0F].to_string(),
+                            count: smash_tv.enemy_count[x],
+                            spawn_timer: smash_tv.spawn_timer[x] as f32 / 60.0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    miniserde::json::to_string(&state)
+}
+
+fn handle_connection(mut stream: TcpStream, shared: Arc<Mutex<Shared>>, running: Arc<AtomicBool>) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(200)));
+    let _ = stream.set_read_timeout(Some(std::time::Duration::from_secs(5))); //for the request, clients that send nothing are dropped
+
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(o) => o,
+        Err(_) => return,
+    });
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    let mut websocket_key = None;
+
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).unwrap_or(0) == 0 || header.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+
+    match (path, websocket_key) {
+        ("/ws", Some(key)) => {
+            let accept = base64(&sha1(format!("{key}258EAFA5-E914-47DA-95CA-C5AB0DC85B11").as_bytes()));
+            let response = format!("HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n");
+
+            if stream.write_all(response.as_bytes()).is_ok() {
+                websocket(stream, reader, &shared, &running);
+            }
+        }
+
+        ("/state", _) => {
+            let state = shared.lock().unwrap().state.clone();
+            respond(&mut stream, "200 OK", "application/json", &state);
+        }
+
+        ("/", _) | ("/overlay.html", _) => respond(&mut stream, "200 OK", "text/html; charset=utf-8", OVERLAY),
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "not found"),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{body}",
+        body.len(),
+    );
+
+    let _ = stream.write_all(response.as_bytes());
+}
+
+//writes queued frames on another thread and answers the client's frames on this one until either side closes
+fn websocket(stream: TcpStream, mut reader: BufReader<TcpStream>, shared: &Mutex<Shared>, running: &AtomicBool) {
+    let (sender, receiver) = mpsc::sync_channel::<Frame>(QUEUE_LEN);
+
+    let mut writer = match stream.try_clone() {
+        Ok(o) => o,
+        Err(_) => return,
+    };
+
+    std::thread::spawn(move || {
+        for frame in receiver {
+            if writer.write_all(&frame).is_err() {
+                break;
+            }
+        }
+
+        //wakes up the reading side when the client stalled or the server stopped
+        let _ = writer.shutdown(Shutdown::Both);
+    });
+
+    let id = {
+        let mut shared2 = shared.lock().unwrap();
+        shared2.next_id += 1;
+        let id = shared2.next_id;
+        shared2.clients.push((id, sender.clone()));
+        id
+    };
+
+    //short timeout so a stopped server is noticed, silent clients are fine as long as writes go through
+    let _ = stream.set_read_timeout(Some(std::time::Duration::from_secs(1)));
+
+    while running.load(Ordering::Relaxed) {
+        let mut first = [0; 1];
+
+        match reader.read(&mut first) {
+            Ok(0) => break,
+            Ok(_) => (),
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => continue,
+            Err(_) => break,
+        }
+
+        let (opcode, payload) = match read_frame(first[0], &mut reader) {
+            Ok(o) => o,
+            Err(_) => break,
+        };
+
+        match opcode {
+            OPCODE_CLOSE => {
+                //echo the status code back, then the writer closes the connection once it has been sent
+                let _ = sender.send(Arc::new(websocket_frame(OPCODE_CLOSE, &payload[.. payload.len().min(2)])));
+                break;
+            }
+
+            //a pong that doesn't fit behind the queued frames is skipped, a writer that failed has shut the stream down already
+            OPCODE_PING => {
+                let _ = sender.try_send(Arc::new(websocket_frame(OPCODE_PONG, &payload)));
+            }
+
+            _ => (), //the overlay doesn't send anything else
+        }
+    }
+
+    //the writer ends after sending what is left once the last sender is gone
+    shared.lock().unwrap().clients.retain(|(id2, _)| *id2 != id);
+}
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+//reads the rest of a client frame after its first byte and unmasks the payload. fragmented frames aren't needed here
+fn read_frame(first: u8, reader: &mut impl Read) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut second = [0; 1];
+    reader.read_exact(&mut second)?;
+
+    let len = match second[0] & 0x7F {
+        126 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as u64
+        }
+
+        127 => {
+            let mut len = [0; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+
+        len => len as u64,
+    };
+
+    if len > MAX_PAYLOAD {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "frame too large"));
+    }
+
+    let mut mask = [0; 4];
+    if second[0] & 0x80 != 0 {
+        reader.read_exact(&mut mask)?;
+    }
+
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+
+    for (x, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[x % 4];
+    }
+
+    Ok((first & 0x0F, payload))
+}
+
+//single unmasked frame
+fn websocket_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+
+    match payload.len() {
+        len @ 0 ..= 125 => frame.push(len as u8),
+        len @ 126 ..= 0xFFFF => {
+            frame.push(126);
+            frame.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend((len as u64).to_be_bytes());
+        }
+    }
+
+    frame.extend(payload);
+    frame
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+
+        for x in 0 .. 16 {
+            w[x] = u32::from_be_bytes([block[x * 4], block[x * 4 + 1], block[x * 4 + 2], block[x * 4 + 3]]);
+        }
+
+        for x in 16 .. 80 {
+            w[x] = (w[x - 3] ^ w[x - 8] ^ w[x - 14] ^ w[x - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+
+        for (x, word) in w.iter().enumerate() {
+            let (f, k) = match x {
+                0 ..= 19 => ((b & c) | (!b & d), 0x5A827999),
+                20 ..= 39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40 ..= 59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (x, value) in [a, b, c, d, e].into_iter().enumerate() {
+            h[x] = h[x].wrapping_add(value);
+        }
+    }
+
+    let mut out = [0; 20];
+    for (x, value) in h.iter().enumerate() {
+        out[x * 4 .. x * 4 + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    out
+}
+
+fn base64(data: &[u8]) -> String {
+    const CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for x in 0 .. 4 {
+            match x <= chunk.len() {
+                true => out.push(CHARS[(n >> (18 - x * 6)) as usize & 0x3F] as char),
+                false => out.push('='),
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn handshake() {
+        //the example from rfc 6455 section 1.3
+        let key = "dGhlIHNhbXBsZSBub25jZQ==";
+        assert_eq!(base64(&sha1(format!("{key}258EAFA5-E914-47DA-95CA-C5AB0DC85B11").as_bytes())), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn sha1_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(hex(&sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")), "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn masked_frames() {
+        //masked "Hello" and an empty ping, both from rfc 6455 section 5.7
+        let mut hello: &[u8] = &[0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+        assert_eq!(read_frame(0x81, &mut hello).unwrap(), (OPCODE_TEXT, b"Hello".to_vec()));
+
+        let mut ping: &[u8] = &[0x80, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(read_frame(0x89, &mut ping).unwrap(), (OPCODE_PING, Vec::new()));
+
+        let mut too_large: &[u8] = &[0xFF, 0, 0, 0, 0, 0, 0x10, 0, 0];
+        assert!(read_frame(0x82, &mut too_large).is_err());
+
+        assert_eq!(websocket_frame(OPCODE_PONG, b"Hello"), [0x8A, 0x05, b'H', b'e', b'l', b'l', b'o']);
+    }
+}
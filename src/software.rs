@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+pub const CLEAR_COLOR: [u8; 4] = [0, 26, 51, 255]; //same as the gl backend's
+
+//cpu rasterizer for egui's meshes, used when the gpu can't give us any usable gl context.
+//draws premultiplied rgba into `pixels`, blended the same way as the gl backend
+pub struct Renderer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>, //rgba, row by row from the top
+    textures: HashMap<egui::TextureId, Texture>,
+}
+
+struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Renderer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0; 4]; width * height],
+            textures: HashMap::new(),
+        }
+    }
+
+    pub fn update_textures(&mut self, tex_set: Vec<(egui::TextureId, egui::epaint::ImageDelta)>) {
+        for (id, image_delta) in tex_set {
+            let pixels: Vec<[u8; 4]> = match &image_delta.image {
+                egui::ImageData::Color(image) => image.pixels.iter().map(|color| color.to_array()).collect(),
+                egui::ImageData::Font(image) => image.srgba_pixels(Some(1.0)).map(|color| color.to_array()).collect(),
+            };
+
+            let width = image_delta.image.width();
+            let height = image_delta.image.height();
+
+            match (image_delta.pos, self.textures.get_mut(&id)) {
+                (Some(pos), Some(texture)) => {
+                    for y in 0 .. height.min(texture.height.saturating_sub(pos[1])) {
+                        for x in 0 .. width.min(texture.width.saturating_sub(pos[0])) {
+                            texture.pixels[(pos[1] + y) * texture.width + pos[0] + x] = pixels[y * width + x];
+                        }
+                    }
+                }
+
+                _ => {
+                    self.textures.insert(id, Texture { width, height, pixels });
+                }
+            }
+        }
+    }
+
+    pub fn free_textures(&mut self, tex_free: &[egui::TextureId]) {
+        for id in tex_free {
+            self.textures.remove(id);
+        }
+    }
+
+    pub fn paint(&mut self, clipped_primitives: &[egui::ClippedPrimitive], width: usize, height: usize, clear: [u8; 4]) {
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.pixels = vec![[0; 4]; width * height];
+        }
+
+        self.pixels.fill(clear);
+
+        for clipped_primitive in clipped_primitives {
+            let mesh = match &clipped_primitive.primitive {
+                egui::epaint::Primitive::Mesh(mesh2) => mesh2,
+                egui::epaint::Primitive::Callback(_) => continue,
+            };
+
+            let clip = clipped_primitive.clip_rect;
+
+            let clip2 = (
+                clip.min.x.max(0.0) as usize,
+                clip.min.y.max(0.0) as usize,
+                (clip.max.x.max(0.0).ceil() as usize).min(width),
+                (clip.max.y.max(0.0).ceil() as usize).min(height),
+            );
+
+            let texture = self.textures.get(&mesh.texture_id);
+
+            for triangle in mesh.indices.chunks_exact(3) {
+                let vertices = [
+                    &mesh.vertices[triangle[0] as usize],
+                    &mesh.vertices[triangle[1] as usize],
+                    &mesh.vertices[triangle[2] as usize],
+                ];
+
+                draw_triangle(&mut self.pixels, width, clip2, texture, vertices);
+            }
+        }
+    }
+}
+
+fn draw_triangle(pixels: &mut [[u8; 4]], width: usize, clip: (usize, usize, usize, usize), texture: Option<&Texture>, vertices: [&egui::epaint::Vertex; 3]) {
+    let [a, b, c] = vertices.map(|vertex| vertex.pos);
+    let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+
+    if area == 0.0 {
+        return;
+    }
+
+    let x_min = (a.x.min(b.x).min(c.x).floor().max(0.0) as usize).max(clip.0);
+    let y_min = (a.y.min(b.y).min(c.y).floor().max(0.0) as usize).max(clip.1);
+    let x_max = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(clip.2);
+    let y_max = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(clip.3);
+
+    let colors = vertices.map(|vertex| vertex.color.to_array().map(|channel| channel as f32));
+
+    for y in y_min .. y_max {
+        for x in x_min .. x_max {
+            let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+
+            //barycentric weights, all positive inside the triangle whichever way it is wound
+            let w0 = ((b.x - p.x) * (c.y - p.y) - (b.y - p.y) * (c.x - p.x)) / area;
+            let w1 = ((c.x - p.x) * (a.y - p.y) - (c.y - p.y) * (a.x - p.x)) / area;
+            let w2 = 1.0 - w0 - w1;
+
+            if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                continue;
+            }
+
+            let uv = vertices[0].uv.to_vec2() * w0 + vertices[1].uv.to_vec2() * w1 + vertices[2].uv.to_vec2() * w2;
+
+            let texel = match texture {
+                Some(texture2) => sample(texture2, uv.x, uv.y),
+                None => [1.0; 4],
+            };
+
+            let mut src = [0.0; 4];
+
+            for channel in 0 .. 4 {
+                let color = colors[0][channel] * w0 + colors[1][channel] * w1 + colors[2][channel] * w2;
+                src[channel] = color * texel[channel];
+            }
+
+            //premultiplied alpha, like BlendFunc(ONE, ONE_MINUS_SRC_ALPHA)
+            let dst = &mut pixels[y * width + x];
+            let keep = 1.0 - src[3] / 255.0;
+
+            for channel in 0 .. 4 {
+                dst[channel] = (src[channel] + dst[channel] as f32 * keep).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+//bilinear, clamped to the edge, returns 0 - 1 per channel
+fn sample(texture: &Texture, u: f32, v: f32) -> [f32; 4] {
+    if texture.width == 0 || texture.height == 0 {
+        return [1.0; 4];
+    }
+
+    let x = (u * texture.width as f32 - 0.5).clamp(0.0, (texture.width - 1) as f32);
+    let y = (v * texture.height as f32 - 0.5).clamp(0.0, (texture.height - 1) as f32);
+
+    let (x0, y0) = (x as usize, y as usize);
+    let (x1, y1) = ((x0 + 1).min(texture.width - 1), (y0 + 1).min(texture.height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    let texel = |x2: usize, y2: usize| texture.pixels[y2 * texture.width + x2];
+    let (t00, t10, t01, t11) = (texel(x0, y0), texel(x1, y0), texel(x0, y1), texel(x1, y1));
+
+    let mut out = [0.0; 4];
+
+    for channel in 0 .. 4 {
+        let top = t00[channel] as f32 * (1.0 - fx) + t10[channel] as f32 * fx;
+        let bottom = t01[channel] as f32 * (1.0 - fx) + t11[channel] as f32 * fx;
+        out[channel] = (top * (1.0 - fy) + bottom * fy) / 255.0;
+    }
+
+    out
+}
+
+//copies the pixels into the window through gdi, which works without any gpu driver
+pub fn present(window: &winit::window::Window, renderer: &Renderer) {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use windows::Win32::{Foundation::HWND, Graphics::Gdi};
+
+    let hwnd = match window.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => HWND(handle.hwnd as isize),
+        _ => return,
+    };
+
+    //gdi wants bgra
+    let bgra: Vec<[u8; 4]> = renderer.pixels.iter().map(|pixel| [pixel[2], pixel[1], pixel[0], 255]).collect();
+
+    let info = Gdi::BITMAPINFO {
+        bmiHeader: Gdi::BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<Gdi::BITMAPINFOHEADER>() as u32,
+            biWidth: renderer.width as i32,
+            biHeight: -(renderer.height as i32), //negative for rows from the top
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: Gdi::BI_RGB,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    unsafe {
+        let hdc = Gdi::GetDC(hwnd);
+
+        Gdi::SetDIBitsToDevice(
+            hdc,
+            0,
+            0,
+            renderer.width as u32,
+            renderer.height as u32,
+            0,
+            0,
+            0,
+            renderer.height as u32,
+            bgra.as_ptr() as *const std::ffi::c_void,
+            &info,
+            Gdi::DIB_RGB_COLORS,
+        );
+
+        Gdi::ReleaseDC(hwnd, hdc);
+    }
+}
@@ -0,0 +1,124 @@
+pub struct RankStats {
+    pub samples: usize,
+    pub duration: f64,
+    pub time_at_level: Vec<f64>, //seconds spent at each rank level
+    pub max: f32,
+    pub time_to_max: f64,
+    pub increases: u32,
+    pub decreases: u32,
+    pub deaths: Vec<(f64, f32)>, //(time, rank before the death)
+    pub last: Option<(f64, f32)>, //newest sample added
+    death_drop: u8,
+    level_counts: Vec<usize>,
+    sum: f64,
+    start: f64,
+}
+
+impl RankStats {
+    //deaths aren't read from the game. instead a drop of at least `death_drop` levels between two
+    //samples counts as one, as the supported shooters lower rank by several levels when the player dies
+    pub fn new(steps: u8, death_drop: u8) -> Self {
+        Self {
+            samples: 0,
+            duration: 0.0,
+            time_at_level: vec![0.0; steps as usize],
+            max: 0.0,
+            time_to_max: 0.0,
+            increases: 0,
+            decreases: 0,
+            deaths: Vec::new(),
+            last: None,
+            death_drop,
+            level_counts: vec![0; steps as usize],
+            sum: 0.0,
+            start: 0.0,
+        }
+    }
+
+    //samples are added one at a time as they come in, so a long session isn't scanned again every frame
+    pub fn add(&mut self, time: f64, value: f32) {
+        match self.last {
+            None => self.start = time,
+
+            Some((last_time, last_value)) => {
+                if let Some(level_time) = self.time_at_level.get_mut(last_value as usize) {
+                    *level_time += time - last_time;
+                }
+
+                if value > last_value {
+                    self.increases += 1;
+                }
+                else if value < last_value {
+                    self.decreases += 1;
+
+                    if self.death_drop > 0 && last_value - value >= self.death_drop as f32 {
+                        self.deaths.push((time, last_value));
+                    }
+                }
+            }
+        }
+
+        if self.samples == 0 || value > self.max {
+            self.max = value;
+            self.time_to_max = time - self.start;
+        }
+
+        if let Some(count) = self.level_counts.get_mut(value as usize) {
+            *count += 1;
+        }
+
+        self.sum += value as f64;
+        self.samples += 1;
+        self.duration = time - self.start;
+        self.last = Some((time, value));
+    }
+
+    pub fn death_drop(&self) -> u8 {
+        self.death_drop
+    }
+
+    pub fn mean(&self) -> f32 {
+        match self.samples {
+            0 => 0.0,
+            samples => (self.sum / samples as f64) as f32,
+        }
+    }
+
+    pub fn median(&self) -> f32 {
+        let mut seen = 0;
+
+        for (level, count) in self.level_counts.iter().enumerate() {
+            seen += count;
+
+            if self.samples > 0 && seen * 2 >= self.samples {
+                return level as f32;
+            }
+        }
+
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_stats() {
+        let mut stats = RankStats::new(8, 3);
+
+        for (time, value) in [(10.0, 1.0), (11.0, 4.0), (13.0, 7.0), (14.0, 2.0), (16.0, 2.0), (17.0, 3.0)] {
+            stats.add(time, value);
+        }
+
+        assert_eq!(stats.samples, 6);
+        assert_eq!(stats.duration, 7.0);
+        assert_eq!((stats.max, stats.time_to_max), (7.0, 3.0));
+        assert_eq!((stats.increases, stats.decreases), (3, 1));
+        assert_eq!(stats.deaths, [(14.0, 7.0)]);
+        assert_eq!(stats.time_at_level[2], 3.0);
+        assert_eq!(stats.mean(), 19.0 / 6.0);
+        assert_eq!(stats.median(), 2.0);
+        assert_eq!(stats.last, Some((17.0, 3.0)));
+    }
+}
@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use game_data_reader::{game_data, update::{CurrentGame, DataTypes}};
+
+//placeholders that can be used in templates
+pub const PLACEHOLDERS: &str = "{game} {rank} {max} {steps} {time} {active_enemies} {waves}";
+
+//a text file for streaming software to read, rewritten whenever its rendered template changes
+pub struct TextFile {
+    pub path: String,
+    pub template: String,
+    pub enabled: bool,
+    pub error: Option<String>,
+    last: Option<String>,
+}
+
+impl TextFile {
+    //starts disabled, nothing is written until the user turns it on
+    pub fn new(path: &str, template: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            template: template.to_string(),
+            enabled: false,
+            error: None,
+            last: None,
+        }
+    }
+
+    pub fn update(&mut self, values: &[(&str, String)]) -> std::io::Result<()> {
+        let text = render(&self.template, values);
+
+        if self.last.as_ref() == Some(&text) {
+            return Ok(());
+        }
+
+        write_atomic(Path::new(self.path.trim()), &text)?;
+        self.last = Some(text);
+        Ok(())
+    }
+}
+
+pub fn values(current_game: &Option<CurrentGame>) -> Vec<(&'static str, String)> {
+    let mut values = vec![
+        ("game", String::new()),
+        ("rank", String::new()),
+        ("max", String::new()),
+        ("steps", String::new()),
+        ("time", String::new()),
+        ("active_enemies", String::new()),
+        ("waves", String::new()),
+    ];
+
+    let mut set = |name: &str, value: String| {
+        if let Some(entry) = values.iter_mut().find(|entry| entry.0 == name) {
+            entry.1 = value;
+        }
+    };
+
+    if let Some(current_game2) = current_game {
+        let seconds = current_game2.start.elapsed().as_secs();
+
+        set("game", current_game2.game.id.id().to_string());
+        set("time", crate::format_time(seconds as f64, 0));
+
+        match &current_game2.game.data_type {
+            DataTypes::Rank(rank) => {
+                let end = rank.history.end();
+
+                if end > rank.history.start() {
+                    set("rank", rank.history.get(end - 1).1.to_string());
+                }
+
+                set("max", (rank.steps - 1).to_string());
+                set("steps", rank.steps.to_string());
+            }
+
+            DataTypes::SmashTV(smash_tv) => {
+                let mut waves = String::new();
+
+                for x in 0 .. 7 {
+                    if smash_tv.enemy_type[x] != 0 {
+                        waves += &format!(
+                            "{} x{} ({:.1}s)\n",
+                            game_data::SMASH_TV_ENEMY_NAMES[smash_tv.enemy_type[x] as usize & 0x0F],
+                            smash_tv.enemy_count[x],
+                            smash_tv.spawn_timer[x] as f32 / 60.0,
+                        );
+                    }
+                }
+
+                set("active_enemies", smash_tv.active_enemies[0].to_string());
+                set("waves", waves.trim_end().to_string());
+            }
+        }
+    }
+
+    values
+}
+
+//replaces {name} with its value, unknown placeholders are left as they are
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[.. open]);
+        rest = &rest[open ..];
+
+        let value = rest.find('}').and_then(|close| {
+            let name = &rest[1 .. close];
+            values.iter().find(|entry| entry.0 == name).map(|entry| (close, &entry.1))
+        });
+
+        match value {
+            Some((close, value)) => {
+                out.push_str(value);
+                rest = &rest[close + 1 ..];
+            }
+
+            None => {
+                out.push('{');
+                rest = &rest[1 ..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out.replace("\\n", "\n")
+}
+
+//writes to a temporary file next to `path` and renames it over, so readers never see a half written file
+fn write_atomic(path: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+
+    std::fs::write(&tmp, text)?;
+    std::fs::rename(&tmp, path)
+}
@@ -0,0 +1,176 @@
+//renders `create_ui` offscreen with the software renderer and compares the frame against the images in tests/golden.
+//after an intended ui change, run `UPDATE_GOLDEN=1 cargo test ui_tests` and check the new images before committing them.
+//frames that don't match are written to target/ui_tests for comparison
+
+use std::path::{Path, PathBuf};
+
+use game_data_reader::{game_data::Games, log, update::{CurrentGame, DataTypes}};
+
+use crate::{args::Args, config, software, GuiState};
+
+const SIZE: (usize, usize) = (800, 600);
+
+//a pixel counts as changed when a channel is off by more than this, small differences come from float rounding
+const TOLERANCE: u8 = 8;
+const MAX_CHANGED: f64 = 0.001; //share of changed pixels before a test fails
+
+fn gui_state() -> GuiState {
+    let dir = std::env::temp_dir().join("game-data-reader-ui-tests");
+    let log = log::Log::new(dir.join("test.log"));
+
+    GuiState::new(config::Save::default(), &Args::default(), log, dir.join("app.cfg"))
+}
+
+//a game with made up rank values, rising and falling so the whole gradient shows
+fn rank_game(game: Games) -> Option<CurrentGame> {
+    let mut current_game = CurrentGame::detached(game.game_info());
+
+    if let DataTypes::Rank(rank) = &mut current_game.game.data_type {
+        let period = (rank.steps as usize - 1) * 2;
+
+        for x in 0 .. 600 {
+            let step = x / 20 % period;
+            rank.history.push(x as f64 * 0.5, step.min(period - step) as f32);
+        }
+    }
+
+    Some(current_game)
+}
+
+fn render(gui_state: &mut GuiState, current_game: &mut Option<CurrentGame>) -> software::Renderer {
+    let mut ctx = egui::Context::default();
+    ctx.set_pixels_per_point(2.0);
+
+    let mut renderer = software::Renderer::new(SIZE.0, SIZE.1);
+
+    //windows size themselves over the first frames and fade in, only the last one is kept
+    for frame in 0 .. 4 {
+        let raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(SIZE.0 as f32, SIZE.1 as f32))),
+            time: Some(frame as f64),
+            ..Default::default()
+        };
+
+        ctx.begin_frame(raw_input);
+        crate::create_ui(&mut ctx, gui_state, current_game);
+        let full_output = ctx.end_frame();
+        let clipped_primitives = ctx.tessellate(full_output.shapes);
+
+        renderer.update_textures(full_output.textures_delta.set);
+        renderer.paint(&clipped_primitives, SIZE.0, SIZE.1, software::CLEAR_COLOR);
+        renderer.free_textures(&full_output.textures_delta.free);
+    }
+
+    renderer
+}
+
+fn check(name: &str, renderer: &software::Renderer) {
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{name}.png"));
+    let pixels: Vec<u8> = renderer.pixels.iter().flatten().copied().collect();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        write_png(&golden, renderer.width, renderer.height, &pixels);
+        return;
+    }
+
+    let expected = match read_png(&golden) {
+        Some(expected2) => expected2,
+        None => panic!("missing or unreadable golden image {}, run with UPDATE_GOLDEN=1 to create it", golden.display()),
+    };
+
+    let changed = match (expected.0, expected.1) == (renderer.width, renderer.height) {
+        true => expected.2.chunks_exact(4).zip(pixels.chunks_exact(4)).filter(|(a, b)| a.iter().zip(b.iter()).any(|(a2, b2)| a2.abs_diff(*b2) > TOLERANCE)).count(),
+        false => renderer.width * renderer.height,
+    };
+
+    if changed as f64 > (renderer.width * renderer.height) as f64 * MAX_CHANGED {
+        let actual = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target").join("ui_tests").join(format!("{name}.png"));
+        write_png(&actual, renderer.width, renderer.height, &pixels);
+        panic!("{name}: {changed} pixels differ from {}, the frame was written to {}", golden.display(), actual.display());
+    }
+}
+
+fn write_png(path: &Path, width: usize, height: usize, rgba: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    let mut encoder = png::Encoder::new(std::fs::File::create(path).unwrap(), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header().unwrap().write_image_data(rgba).unwrap();
+}
+
+//(width, height, rgba), none if the file is missing or not 8 bit rgba
+fn read_png(path: &Path) -> Option<(usize, usize, Vec<u8>)> {
+    let mut reader = png::Decoder::new(std::fs::File::open(path).ok()?).read_info().ok()?;
+    let mut rgba = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut rgba).ok()?;
+
+    match (info.color_type, info.bit_depth) {
+        (png::ColorType::Rgba, png::BitDepth::Eight) => Some((info.width as usize, info.height as usize, rgba)),
+        _ => None,
+    }
+}
+
+#[test]
+fn searching() {
+    let mut gui_state = gui_state();
+    check("searching", &render(&mut gui_state, &mut None));
+}
+
+#[test]
+fn rank_graph() {
+    let mut gui_state = gui_state();
+    check("rank_graph", &render(&mut gui_state, &mut rank_game(Games::Gradius3Snes)));
+}
+
+#[test]
+fn rank_graph_overlay() {
+    let mut gui_state = gui_state();
+    gui_state.overlay = true;
+    check("rank_graph_overlay", &render(&mut gui_state, &mut rank_game(Games::ParodiusSnes)));
+}
+
+#[test]
+fn smash_tv() {
+    let mut gui_state = gui_state();
+    let mut current_game = CurrentGame::detached(Games::SmashTVSnes.game_info());
+
+    if let DataTypes::SmashTV(smash_tv) = &mut current_game.game.data_type {
+        smash_tv.enemy_type = [1, 2, 3, 0, 0, 0, 0];
+        smash_tv.enemy_count = [12, 4, 30, 0, 0, 0, 0];
+        smash_tv.spawn_timer = [60, 120, 600, 0, 0, 0, 0];
+        smash_tv.active_enemies = [17];
+    }
+
+    check("smash_tv", &render(&mut gui_state, &mut Some(current_game)));
+}
+
+//saved windows come back at the size they were saved with. only windows whose contents fill the available
+//space, like the log, can be taller than their contents
+#[test]
+fn windows_restore() {
+    let mut gui_state = gui_state();
+    gui_state.log_open = true;
+    render(&mut gui_state, &mut rank_game(Games::Gradius3Snes));
+
+    let mut restored = self::gui_state();
+    restored.log_open = true;
+    restored.windows = gui_state.windows.clone();
+    render(&mut restored, &mut rank_game(Games::Gradius3Snes));
+
+    for (saved, restored2) in gui_state.windows.iter().zip(&restored.windows) {
+        assert_eq!(saved.id, restored2.id);
+        assert_eq!(saved.pos, restored2.pos, "{}", saved.id);
+        assert_eq!(saved.width, restored2.width, "{}", saved.id);
+        assert_eq!(saved.height, restored2.height, "{}", saved.id);
+    }
+
+    let log = restored.windows.iter_mut().find(|saved| saved.id == "log").unwrap();
+    log.width += 40.0;
+    log.height = log.height.map(|height| height + 40.0);
+    let resized = (log.width, log.height);
+
+    render(&mut restored, &mut rank_game(Games::Gradius3Snes));
+    let log2 = restored.windows.iter().find(|saved| saved.id == "log").unwrap();
+    assert_eq!((log2.width, log2.height), resized);
+}
@@ -9,17 +9,56 @@ use windows::{
     },
 };
 
-use crate::game_data;
+use crate::{game_data, history::History, log::Log};
 use std::ffi::c_void;
 
 pub struct CurrentGame {
     pub game: game_data::GameData,
+    pub start: std::time::Instant,
     handle: HANDLE,
+    close: fn(HANDLE), //run on drop. a no-op for detached games, which own no handle
     offset: u64,
+    pointer_chain: Option<(u64, Vec<u64>)>, //(dll base, offsets) for games found through a pointer chain
+}
+
+impl CurrentGame {
+    //a game without an emulator behind it, for rendering the ui in tests. it must not be updated
+    #[cfg(feature = "test-support")]
+    pub fn detached(game: game_data::GameData) -> Self {
+        Self {
+            game,
+            start: std::time::Instant::now(),
+            handle: HANDLE::default(),
+            close: |_| {},
+            offset: 0,
+            pointer_chain: None,
+        }
+    }
+}
+
+//the process handle is owned by the game, so detaching or replacing it closes the handle
+impl Drop for CurrentGame {
+    fn drop(&mut self) {
+        (self.close)(self.handle);
+    }
+}
+
+fn close_handle(handle: HANDLE) {
+    if handle.0 != 0 {
+        unsafe{ CloseHandle(handle); }
+    }
+}
+
+pub struct Sample {
+    pub time: f64, //seconds since the game was found
+    pub frame: Option<u64>,
+    pub name: String,
+    pub raw: u32,
+    pub value: f32,
 }
 
 pub struct Rank {
-    pub data_points: std::collections::VecDeque<f32>,
+    pub history: History,
     pub offset: u16,
     pub steps: u8,
 }
@@ -37,12 +76,24 @@ pub enum DataTypes {
     SmashTV(SmashTV),
 }
 
-pub fn find_game() -> Option<CurrentGame> {
+//limits which processes and games find_game attaches to, anything left as none matches
+#[derive(Default)]
+pub struct Filter {
+    pub pid: Option<u32>,
+    pub emulator: Option<game_data::Emulator>,
+    pub game: Option<String>, //game id, with or without the platform suffix (e.g. "gradius3" or "gradius3_snes")
+}
+
+pub(crate) fn find_game(log: &mut Log, filter: &Filter) -> Option<CurrentGame> {
     let mut emu_info = None;
 
     let (pid_list, pid_count) = enum_processes();
 
     for x in 0 .. pid_count {
+        if filter.pid.is_some_and(|pid| pid != pid_list[x as usize]) {
+            continue;
+        }
+
         unsafe {
             let handle_result = OpenProcess(PROCESS_VM_READ | PROCESS_QUERY_INFORMATION, false, pid_list[x as usize]);
             if let Ok(handle) = handle_result {
@@ -65,7 +116,7 @@ pub fn find_game() -> Option<CurrentGame> {
                     Err(e) => panic!("failed to get convert module name to string: {e}"),
                 };
 
-                if let Some(emu2) = emu {
+                if let Some(emu2) = emu.filter(|emu2| filter.emulator.as_ref().is_none_or(|emulator| emulator == emu2)) {
                     emu_info = Some((emu2, handle));
                 }
                 else {
@@ -83,38 +134,56 @@ pub fn find_game() -> Option<CurrentGame> {
         let mut info = MODULEINFO::default();
         unsafe{ K32GetModuleInformation(handle, first_module, &mut info, std::mem::size_of::<MODULEINFO>() as u32); }
 
-        match get_game_name(handle, &info, &emu) {
-            Some(game) => {
-                Some(CurrentGame {
-                    game: game.game_info(),
-                    handle: handle,
-                    offset: match emu {
-                        game_data::Emulator::Bsnes => 0xB16D7C,
-
-                        game_data::Emulator::Mame => {
-                            match game_data::Emulator::get_mame_version(info.SizeOfImage) {
-                                Ok(version) => {
-                                    match game_data::Emulator::mame_game_offset(version, game) {
-                                        Some(offset_list) => get_mame_offset(handle, info.lpBaseOfDll as u64, offset_list),
-                                        None => {
-                                            unsafe{ CloseHandle(handle); }
-                                            return None; //unsupported mame version. kinda bootleg way to do this
-                                        }
+        match get_game_name(handle, &info, &emu, log) {
+            Some(game) if filter.game.as_ref().is_none_or(|name| game.matches(name)) => {
+                let game_id = game.id();
+                let game_info = game.game_info();
+                let mut pointer_chain = None;
+
+                let offset = match emu {
+                    game_data::Emulator::Bsnes => 0xB16D7C,
+
+                    game_data::Emulator::Mame => {
+                        match game_data::Emulator::get_mame_version(info.SizeOfImage) {
+                            Ok(version) => {
+                                match game_data::Emulator::mame_game_offset(version, game) {
+                                    Some(offset_list) => {
+                                        let offset = get_mame_offset(handle, info.lpBaseOfDll as u64, &offset_list);
+                                        log.info(format!("resolved pointer chain for {game_id}: 0x{offset:X}"));
+                                        pointer_chain = Some((info.lpBaseOfDll as u64, offset_list));
+                                        offset
                                     }
-                                }
 
-                                Err(_) => {
-                                    println!("{:X}", info.SizeOfImage);
-                                    unsafe{ CloseHandle(handle); }
-                                    return None; //unsupported mame version. kinda bootleg way to do this
+                                    None => {
+                                        log.warning(format!("{game_id} is not supported on mame {version}"));
+                                        unsafe{ CloseHandle(handle); }
+                                        return None; //unsupported mame version. kinda bootleg way to do this
+                                    }
                                 }
                             }
+
+                            Err(_) => {
+                                log.warning(format!("unsupported mame version (module size 0x{:X})", info.SizeOfImage));
+                                unsafe{ CloseHandle(handle); }
+                                return None; //unsupported mame version. kinda bootleg way to do this
+                            }
                         }
-                    },
+                    }
+                };
+
+                log.info(format!("attached to {game_id}"));
+
+                Some(CurrentGame {
+                    game: game_info,
+                    start: std::time::Instant::now(),
+                    handle: handle,
+                    close: close_handle,
+                    offset,
+                    pointer_chain,
                 })
             }
 
-            None => {
+            _ => {
                 unsafe{ CloseHandle(handle); }
                 None
             }
@@ -133,10 +202,13 @@ fn enum_processes() -> ([u32; 384], u32) {
     (pid_list, pid_size / 4)
 }
 
-fn get_game_name(handle: HANDLE, info: &MODULEINFO, emu: &game_data::Emulator) -> Option<game_data::Games> {
+fn get_game_name(handle: HANDLE, info: &MODULEINFO, emu: &game_data::Emulator, log: &mut Log) -> Option<game_data::Games> {
     let game_name_offset = match emu.name_offset(info.SizeOfImage, info.lpBaseOfDll as u64) {
         Ok(offset) => offset as *const c_void,
-        Err(_) => return None,
+        Err(_) => {
+            log.warning(format!("unsupported mame version (module size 0x{:X})", info.SizeOfImage));
+            return None;
+        }
     };
 
     let mut raw_str = [0; 22];
@@ -154,9 +226,12 @@ fn get_game_name(handle: HANDLE, info: &MODULEINFO, emu: &game_data::Emulator) -
     }
 }
 
-fn get_mame_offset(handle: HANDLE, dll_base: u64, offset_list: Vec<u64>) -> u64 {
+fn get_mame_offset(handle: HANDLE, dll_base: u64, offset_list: &[u64]) -> u64 {
     std::thread::sleep(std::time::Duration::from_secs(2)); //sleep because getting the offset while mame is loading the game can fail
+    resolve_pointer_chain(handle, dll_base, offset_list)
+}
 
+fn resolve_pointer_chain(handle: HANDLE, dll_base: u64, offset_list: &[u64]) -> u64 {
     unsafe {
         let mut address = dll_base;
 
@@ -170,7 +245,7 @@ fn get_mame_offset(handle: HANDLE, dll_base: u64, offset_list: Vec<u64>) -> u64
     }
 }
 
-pub fn check_still_running(current_game: &mut CurrentGame) -> bool {
+pub(crate) fn check_still_running(current_game: &mut CurrentGame) -> bool {
     //check if game window is closed. not perfect as user can load other game without closing the emulator
     //todo: check for string again probably
     let mut exit_code = 0;
@@ -179,50 +254,90 @@ pub fn check_still_running(current_game: &mut CurrentGame) -> bool {
     exit_code == STILL_ACTIVE.0 as u32
 }
 
-pub fn update(current_game: &mut CurrentGame) {
-    match &mut current_game.game.data_type {
-        DataTypes::Rank(rank) => update_rank(rank, &current_game.game.id, current_game.handle, current_game.offset),
-        DataTypes::SmashTV(smash_tv) => update_smash_tv(smash_tv, current_game.handle, current_game.offset),
+pub(crate) fn update(current_game: &mut CurrentGame, log: &mut Log) -> Vec<Sample> {
+    let time = current_game.start.elapsed().as_secs_f64();
+
+    let samples = match &mut current_game.game.data_type {
+        DataTypes::Rank(rank) => update_rank(rank, &current_game.game.id, current_game.handle, current_game.offset, time, log),
+        DataTypes::SmashTV(smash_tv) => update_smash_tv(smash_tv, current_game.handle, current_game.offset, time, log),
+    };
+
+    match samples {
+        Some(samples2) => samples2,
+
+        None => {
+            //mame can move the game's memory around (e.g. on a reset), so follow the pointer chain again
+            if let Some((dll_base, offset_list)) = &current_game.pointer_chain {
+                let offset = resolve_pointer_chain(current_game.handle, *dll_base, offset_list);
+
+                if offset != current_game.offset {
+                    log.info(format!("re-resolved pointer chain: 0x{:X} -> 0x{offset:X}", current_game.offset));
+                    current_game.offset = offset;
+                }
+            }
+
+            Vec::new()
+        }
     }
 }
 
-fn update_rank(rank: &mut Rank, game: &game_data::Games, handle: HANDLE, base_offset: u64) {
+fn update_rank(rank: &mut Rank, game: &game_data::Games, handle: HANDLE, base_offset: u64, time: f64, log: &mut Log) -> Option<Vec<Sample>> {
     let mut temp_rank = 0;
 
-    unsafe {
+    let read = unsafe {
         let base = (base_offset + rank.offset as u64) as *const c_void;
         let p_rank = &mut temp_rank as *mut _ as *mut c_void;
-        ReadProcessMemory(handle, base, p_rank, 1, None);
+        ReadProcessMemory(handle, base, p_rank, 1, None)
+    };
+
+    if !read.as_bool() {
+        log.error(format!("unable to read rank at 0x{:X}", base_offset + rank.offset as u64));
+        return None;
     }
 
+    let raw = temp_rank;
     temp_rank = game.format_rank(temp_rank);
 
     if temp_rank >= rank.steps {
-        println!("rank out of range: {}", temp_rank); //todo: maybe log to some misc log window instead
+        log.warning(format!("rank out of range: {temp_rank} (raw byte 0x{raw:02X})"));
         temp_rank = 0;
     }
 
-    rank.data_points.pop_front();
-    rank.data_points.push_back(temp_rank as f32);
-    rank.data_points.make_contiguous();
+    let end = rank.history.end();
+    if end > rank.history.start() {
+        let (_, last) = rank.history.get(end - 1);
+
+        if last != temp_rank as f32 {
+            log.info(format!("{} at {:.1}s: rank {last} -> {temp_rank}", game.id(), time));
+        }
+    }
+
+    rank.history.push(time, temp_rank as f32);
+
+    Some(vec![Sample { time, frame: None, name: "rank".to_string(), raw: raw as u32, value: temp_rank as f32 }])
 }
 
-fn update_smash_tv(smash_tv: &mut SmashTV, handle: HANDLE, base_offset: u64) {
+fn update_smash_tv(smash_tv: &mut SmashTV, handle: HANDLE, base_offset: u64, time: f64, log: &mut Log) -> Option<Vec<Sample>> {
     const LIST_COUNT: usize = 7 * 10;
     let mut temp = [0; LIST_COUNT];
 
-    unsafe {
+    let read = unsafe {
         let base = (base_offset + 0x1902) as *const c_void;
         let p_rank = temp.as_mut_ptr() as *mut _ as *mut c_void;
-        ReadProcessMemory(handle, base, p_rank, LIST_COUNT, None);
-    }
+        ReadProcessMemory(handle, base, p_rank, LIST_COUNT, None)
+    };
 
     let mut temp2 = 0;
 
-    unsafe {
+    let read2 = unsafe {
         let base = (base_offset + 0x18E4) as *const c_void;
         let p_rank = &mut temp2 as *mut _ as *mut c_void;
-        ReadProcessMemory(handle, base, p_rank, 1, None);
+        ReadProcessMemory(handle, base, p_rank, 1, None)
+    };
+
+    if !read.as_bool() || !read2.as_bool() {
+        log.error(format!("unable to read smash tv enemy lists at 0x{:X}", base_offset));
+        return None;
     }
 
     smash_tv.active_enemies[0] = temp2;
@@ -233,4 +348,20 @@ fn update_smash_tv(smash_tv: &mut SmashTV, handle: HANDLE, base_offset: u64) {
         smash_tv.enemy_count[y] = u16::from_le_bytes([temp[7 * 1 + x], temp[7 * 2 + x]]);
         smash_tv.spawn_timer[y] = u16::from_le_bytes([temp[7 * 8 + x], temp[7 * 9 + x]]);
     }
+
+    let mut samples = vec![Sample { time, frame: None, name: "active_enemies".to_string(), raw: temp2 as u32, value: temp2 as f32 }];
+
+    for x in 0 .. 7 {
+        let values = [
+            ("enemy_type", smash_tv.enemy_type[x] as u16),
+            ("enemy_count", smash_tv.enemy_count[x]),
+            ("spawn_timer", smash_tv.spawn_timer[x]),
+        ];
+
+        for (name, value) in values {
+            samples.push(Sample { time, frame: None, name: format!("{name}_{x}"), raw: value as u32, value: value as f32 });
+        }
+    }
+
+    Some(samples)
 }